@@ -0,0 +1,99 @@
+//! Measures how quickly programs of different shapes parse. Each program is generated to
+//! have the same number of nodes, so that results can be compared from run to run, and
+//! from one shape to another.
+//!
+//! Run with `cargo bench`, optionally passing the name of a shape to only measure that one.
+
+use std::env;
+use std::hint::black_box;
+use std::time::Duration;
+use std::time::Instant;
+use yall::parser;
+use yall::parser::Parser;
+
+/// Generates a program with `NODES` nodes.
+type Generate = fn() -> String;
+
+/// How many nodes each generated program has.
+const NODES: usize = 100_000;
+
+/// How long to keep parsing each program for, to smooth out any noise.
+const MEASURE_FOR: Duration = Duration::from_secs(3);
+
+/// Nested items, each as deep as the parser allows by default.
+fn nested() -> String {
+	let depth = parser::DEFAULT_MAX_DEPTH - 1;
+	// Each level is an item holding an identifier, and the next level
+	let tower = format!("{}{}", "(a ".repeat(depth), ")".repeat(depth));
+	let towers = NODES / (depth * 2);
+	tower.repeat(towers)
+}
+
+/// Lists of strings, some of which need their escapes decoded.
+fn strings() -> String {
+	// Each item is itself a node, along with the eight strings inside of it
+	let item = r#"["hello" "friend" "with \"escapes\"\n" "longer strings take longer" "" "a" "\u{1F600}" r"raw \n"]"#;
+	let mut source = String::new();
+	for _ in 0..NODES / 9 {
+		source.push_str(item);
+		source.push('\n');
+	}
+	source
+}
+
+/// Lists of numbers, in every way they can be written.
+fn numbers() -> String {
+	let item = "[0 42 -7 1_000_000 0xff 0b1010 2.5 .5 -1e3]";
+	let mut source = String::new();
+	for _ in 0..NODES / 10 {
+		source.push_str(item);
+		source.push('\n');
+	}
+	source
+}
+
+fn measure(name: &str, source: &str) {
+	let parse = || {
+		let program = parser::parse_program(&mut Parser::from(source)).unwrap();
+		black_box(program);
+	};
+
+	// Make sure everything is warmed up before anything is measured
+	parse();
+
+	let start = Instant::now();
+	let mut iterations = 0;
+	while start.elapsed() < MEASURE_FOR {
+		parse();
+		iterations += 1;
+	}
+	let each = start.elapsed() / iterations;
+
+	println!(
+		"{:<8} {:>10.3?} per parse, {:>7.1} ns per node, {:>7.1} MB/s",
+		name,
+		each,
+		each.as_nanos() as f64 / NODES as f64,
+		source.len() as f64 / each.as_secs_f64() / 1_000_000.0,
+	);
+}
+
+fn main() {
+	// `cargo bench` passes `--bench`, which isn't the name of anything
+	let filter = env::args().skip(1).find(|arg| !arg.starts_with('-'));
+	let shapes: [(&str, Generate); 3] = [
+		("nested", nested),
+		("strings", strings),
+		("numbers", numbers),
+	];
+
+	for (name, generate) in shapes {
+		if filter
+			.as_deref()
+			.is_some_and(|filter| !name.contains(filter))
+		{
+			continue;
+		}
+		measure(name, &generate());
+	}
+}
@@ -0,0 +1,130 @@
+//! Errors in a shape that's easy for other tools, like editors, to consume.
+
+use crate::parser::Location;
+use crate::ParseError;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Severity {
+	Error,
+	/// Something that parses fine, but is likely to be a mistake.
+	Warning,
+	/// Something worth knowing about, which isn't a problem with the program itself.
+	Note,
+}
+
+/// A place in the source, counting rows and columns from zero.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Position {
+	pub row: u32,
+	pub col: u32,
+}
+
+impl From<Location> for Position {
+	fn from(location: Location) -> Self {
+		Self {
+			row: location.line,
+			col: location.column,
+		}
+	}
+}
+
+/// A single problem found in the source, and the region it covers. Errors about a
+/// single character start and end at the same position.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Diagnostic {
+	pub severity: Severity,
+	pub message: String,
+	pub start: Position,
+	pub end: Position,
+	/// Other places in the source that have something to do with the problem.
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+	pub related: Vec<Related>,
+}
+
+/// Another region of the source that a diagnostic refers to, like where something it's
+/// about was first written.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Related {
+	pub message: String,
+	pub start: Position,
+	pub end: Position,
+}
+
+impl From<&ParseError> for Diagnostic {
+	fn from(error: &ParseError) -> Self {
+		let span = error.span();
+		Self {
+			severity: match error {
+				ParseError::TooManyErrors { .. } => Severity::Note,
+				_ => Severity::Error,
+			},
+			message: error.to_string(),
+			start: span.start.into(),
+			end: span.end.into(),
+			related: vec![],
+		}
+	}
+}
+
+/// Parses `source`, recovering from as many errors as it can, and describes each of them.
+pub fn parse_diagnostics(source: &str) -> Vec<Diagnostic> {
+	crate::parse_with_recovery(source)
+		.errors
+		.iter()
+		.map(Diagnostic::from)
+		.collect()
+}
+
+#[cfg(test)]
+mod parse_diagnostics_tests {
+	use super::*;
+
+	#[test]
+	fn two_errors() {
+		let source = "(a)\n  (b ]\n(c)\n(d 'ef')\n";
+		let diagnostics = parse_diagnostics(source);
+		assert_eq!(
+			diagnostics,
+			vec![
+				Diagnostic {
+					severity: Severity::Error,
+					message:
+						"mismatched delimiter, expression opened with ( at 2:3 but closed with ]"
+							.to_string(),
+					start: Position { row: 1, col: 5 },
+					end: Position { row: 1, col: 5 },
+					related: vec![],
+				},
+				Diagnostic {
+					severity: Severity::Error,
+					message: "character literal may only contain one character".to_string(),
+					start: Position { row: 3, col: 3 },
+					end: Position { row: 3, col: 3 },
+					related: vec![],
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn ranges() {
+		let diagnostics = parse_diagnostics("(a \"bc");
+		assert_eq!(diagnostics[0].start, Position { row: 0, col: 3 });
+		assert_eq!(diagnostics[0].end, Position { row: 0, col: 6 });
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn json() {
+		let diagnostics = parse_diagnostics("(a \"bc");
+		assert_eq!(
+			serde_json::to_string(&diagnostics).unwrap(),
+			r#"[{"severity":"error","message":"unterminated string","start":{"row":0,"col":3},"end":{"row":0,"col":6}}]"#
+		);
+	}
+}
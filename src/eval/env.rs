@@ -0,0 +1,24 @@
+use std::collections::HashMap;
+
+use super::Value;
+
+/// The variables in scope during evaluation.
+#[derive(Clone, Debug, Default)]
+pub struct Env {
+	variables: HashMap<String, Value>,
+}
+
+impl Env {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Defines `name`, replacing any previous definition.
+	pub fn define(&mut self, name: impl Into<String>, value: Value) {
+		self.variables.insert(name.into(), value);
+	}
+
+	pub fn get(&self, name: &str) -> Option<&Value> {
+		self.variables.get(name)
+	}
+}
@@ -0,0 +1,37 @@
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::Span;
+
+#[derive(Clone, Debug, Diagnostic, Eq, Error, PartialEq)]
+pub enum EvalError {
+	#[error("{name} is not defined")]
+	UndefinedVariable { name: String, span: Span },
+
+	#[error("expected {expected}, found {found}")]
+	TypeMismatch {
+		expected: &'static str,
+		found: &'static str,
+		span: Span,
+	},
+
+	#[error("{name} expects {expected} arguments, but was given {found}")]
+	WrongArgumentCount {
+		name: String,
+		expected: &'static str,
+		found: usize,
+		span: Span,
+	},
+
+	#[error("{found} can't be called")]
+	NotCallable { found: &'static str, span: Span },
+
+	#[error("dotted pairs can't be evaluated")]
+	DottedPair { span: Span },
+
+	#[error("division by zero")]
+	DivisionByZero { span: Span },
+
+	#[error("arithmetic overflow")]
+	Overflow { span: Span },
+}
@@ -0,0 +1,278 @@
+//! A tree-walking interpreter for a small core of the language.
+
+mod env;
+mod error;
+mod value;
+
+pub use env::Env;
+pub use error::EvalError;
+pub use value::Value;
+
+use crate::Expression;
+use crate::ExpressionKind;
+use crate::Number;
+use crate::Phrase;
+use crate::Span;
+use crate::Spanned;
+
+/// Evaluates a single expression. Blocks evaluate to their last value, lists to a list
+/// of their values, and items are calls to one of the built-in forms.
+pub fn eval(expression: &Expression, env: &mut Env) -> Result<Value, EvalError> {
+	let values = expression
+		.values
+		.iter()
+		.filter(|value| !matches!(value.node, Phrase::Comment(_)))
+		.collect::<Vec<_>>();
+
+	if expression.tail.is_some() {
+		return Err(EvalError::DottedPair {
+			span: expression.span,
+		});
+	}
+
+	match expression.kind {
+		ExpressionKind::Block => {
+			let mut result = Value::Nil;
+			for value in values {
+				result = eval_phrase(value, env)?;
+			}
+			Ok(result)
+		}
+		ExpressionKind::List => values
+			.into_iter()
+			.map(|value| eval_phrase(value, env))
+			.collect::<Result<_, _>>()
+			.map(Value::List),
+		ExpressionKind::Item => eval_item(&values, expression.span, env),
+	}
+}
+
+/// Evaluates each expression of a program in turn, returning the value of the last.
+pub fn eval_program(program: &[Expression], env: &mut Env) -> Result<Value, EvalError> {
+	let mut result = Value::Nil;
+	for expression in program {
+		result = eval(expression, env)?;
+	}
+	Ok(result)
+}
+
+fn eval_phrase(phrase: &Spanned<Phrase>, env: &mut Env) -> Result<Value, EvalError> {
+	Ok(match &phrase.node {
+		Phrase::Expression(expression) => return eval(expression, env),
+		Phrase::Identifier(name) => lookup(name, phrase.span, env)?,
+		Phrase::Symbol(symbol) => lookup(symbol.name(), phrase.span, env)?,
+		Phrase::Text(text) => Value::Text(text.clone()),
+		Phrase::Char(c) => Value::Char(*c),
+		Phrase::Number(number) => Value::Number(*number),
+		Phrase::Keyword(keyword) => Value::Keyword(keyword.clone()),
+		Phrase::Boolean(boolean) => Value::Boolean(*boolean),
+		Phrase::Comment(_) | Phrase::Nil => Value::Nil,
+	})
+}
+
+fn lookup(name: &str, span: Span, env: &Env) -> Result<Value, EvalError> {
+	match env.get(name) {
+		Some(value) => Ok(value.clone()),
+		None => Err(EvalError::UndefinedVariable {
+			name: name.to_string(),
+			span,
+		}),
+	}
+}
+
+fn eval_item(values: &[&Spanned<Phrase>], span: Span, env: &mut Env) -> Result<Value, EvalError> {
+	let Some((head, arguments)) = values.split_first() else {
+		return Ok(Value::Nil);
+	};
+
+	let name = match &head.node {
+		Phrase::Identifier(name) => name.as_str(),
+		Phrase::Symbol(symbol) => symbol.name(),
+		_ => {
+			return Err(EvalError::NotCallable {
+				found: eval_phrase(head, env)?.type_name(),
+				span: head.span,
+			})
+		}
+	};
+
+	match name {
+		"if" => {
+			let [condition, then, otherwise] = arguments else {
+				return Err(EvalError::WrongArgumentCount {
+					name: name.to_string(),
+					expected: "3",
+					found: arguments.len(),
+					span,
+				});
+			};
+			match eval_phrase(condition, env)?.is_truthy() {
+				true => eval_phrase(then, env),
+				false => eval_phrase(otherwise, env),
+			}
+		}
+		"+" | "-" | "*" | "/" => {
+			let mut numbers = Vec::with_capacity(arguments.len());
+			for argument in arguments {
+				match eval_phrase(argument, env)? {
+					Value::Number(number) => numbers.push((number, argument.span)),
+					value => {
+						return Err(EvalError::TypeMismatch {
+							expected: "a number",
+							found: value.type_name(),
+							span: argument.span,
+						})
+					}
+				}
+			}
+			arithmetic(name, &numbers, span).map(Value::Number)
+		}
+		_ => match env.get(name) {
+			Some(value) => Err(EvalError::NotCallable {
+				found: value.type_name(),
+				span: head.span,
+			}),
+			None => Err(EvalError::UndefinedVariable {
+				name: name.to_string(),
+				span: head.span,
+			}),
+		},
+	}
+}
+
+/// Folds `numbers` together with `operator`, like `(- 10 2 3)`. Given no numbers, `+`
+/// and `*` return their identity, and given one, `-` negates it and `/` inverts it.
+fn arithmetic(operator: &str, numbers: &[(Number, Span)], span: Span) -> Result<Number, EvalError> {
+	let (first, rest) = match (operator, numbers) {
+		("+", _) => (Number::Integer(0), numbers),
+		("*", _) => (Number::Integer(1), numbers),
+		(_, []) => {
+			return Err(EvalError::WrongArgumentCount {
+				name: operator.to_string(),
+				expected: "at least 1",
+				found: 0,
+				span,
+			})
+		}
+		("-", [_]) => (Number::Integer(0), numbers),
+		("/", [_]) => (Number::Integer(1), numbers),
+		(_, [(first, _), rest @ ..]) => (*first, rest),
+	};
+
+	rest.iter().try_fold(first, |result, (number, span)| {
+		apply(operator, result, *number, *span)
+	})
+}
+
+/// Applies a single arithmetic operator. Integers stay integers, dividing with
+/// truncation, and anything involving a float is done as a float. `span` is where
+/// `b` came from, which is usually what's to blame when something goes wrong.
+fn apply(operator: &str, a: Number, b: Number, span: Span) -> Result<Number, EvalError> {
+	if let (Number::Integer(a), Number::Integer(b)) = (a, b) {
+		let result = match operator {
+			"+" => a.checked_add(b),
+			"-" => a.checked_sub(b),
+			"*" => a.checked_mul(b),
+			_ if b == 0 => return Err(EvalError::DivisionByZero { span }),
+			_ => a.checked_div(b),
+		};
+		return result
+			.map(Number::Integer)
+			.ok_or(EvalError::Overflow { span });
+	}
+
+	let (a, b) = (as_float(a), as_float(b));
+	let result = match operator {
+		"+" => a + b,
+		"-" => a - b,
+		"*" => a * b,
+		_ if b == 0.0 => return Err(EvalError::DivisionByZero { span }),
+		_ => a / b,
+	};
+
+	// Keep floats finite, so that a `Number` can still never be NaN
+	match result.is_finite() {
+		true => Ok(Number::Float(result)),
+		false => Err(EvalError::Overflow { span }),
+	}
+}
+
+fn as_float(number: Number) -> f64 {
+	match number {
+		Number::Integer(i) => i as f64,
+		Number::Float(f) => f,
+	}
+}
+
+#[cfg(test)]
+mod eval_tests {
+	use super::*;
+	use crate::parse;
+
+	fn run(source: &str) -> Result<Value, EvalError> {
+		eval_program(&parse(source).unwrap(), &mut Env::new())
+	}
+
+	#[test]
+	fn arithmetic() {
+		assert_eq!(run("(+ 1 2)"), Ok(Value::Number(Number::Integer(3))));
+		assert_eq!(run("(- 10 2 3)"), Ok(Value::Number(Number::Integer(5))));
+		assert_eq!(run("(- 4)"), Ok(Value::Number(Number::Integer(-4))));
+		assert_eq!(
+			run("(* 2 (+ 1 0.5))"),
+			Ok(Value::Number(Number::Float(3.0)))
+		);
+		assert_eq!(run("(/ 7 2)"), Ok(Value::Number(Number::Integer(3))));
+		assert!(matches!(
+			run("(/ 1 0)"),
+			Err(EvalError::DivisionByZero { .. })
+		));
+		assert!(matches!(
+			run("(+ 9223372036854775807 1)"),
+			Err(EvalError::Overflow { .. })
+		));
+		assert!(matches!(
+			run(r#"(+ 1 "2")"#),
+			Err(EvalError::TypeMismatch {
+				expected: "a number",
+				found: "a string",
+				..
+			})
+		));
+	}
+
+	#[test]
+	fn conditionals() {
+		assert_eq!(run("(if true 1 2)"), Ok(Value::Number(Number::Integer(1))));
+		assert_eq!(run("(if nil 1 2)"), Ok(Value::Number(Number::Integer(2))));
+		assert_eq!(
+			run(r#"(if 0 "yes" "no")"#),
+			Ok(Value::Text("yes".to_string()))
+		);
+		assert!(matches!(
+			run("(if true 1)"),
+			Err(EvalError::WrongArgumentCount { found: 2, .. })
+		));
+	}
+
+	#[test]
+	fn variables() {
+		let mut env = Env::new();
+		env.define("x", Value::Number(Number::Integer(20)));
+		let program = parse("{(+ x 1) [x :name]}").unwrap();
+		assert_eq!(
+			eval_program(&program, &mut env),
+			Ok(Value::List(vec![
+				Value::Number(Number::Integer(20)),
+				Value::Keyword("name".to_string()),
+			]))
+		);
+		assert_eq!(
+			run("(+ y 1)"),
+			Err(EvalError::UndefinedVariable {
+				name: "y".to_string(),
+				span: parse("(+ y 1)").unwrap()[0].values[1].span,
+			})
+		);
+	}
+}
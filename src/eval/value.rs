@@ -0,0 +1,61 @@
+use std::fmt;
+use std::fmt::Display;
+
+use crate::Number;
+use crate::Phrase;
+
+/// The result of evaluating something.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+	Nil,
+	Boolean(bool),
+	Number(Number),
+	Text(String),
+	Char(char),
+	Keyword(String),
+	List(Vec<Value>),
+}
+
+impl Value {
+	/// The name of this kind of value, for error messages.
+	pub fn type_name(&self) -> &'static str {
+		match self {
+			Self::Nil => "nil",
+			Self::Boolean(_) => "a boolean",
+			Self::Number(_) => "a number",
+			Self::Text(_) => "a string",
+			Self::Char(_) => "a character",
+			Self::Keyword(_) => "a keyword",
+			Self::List(_) => "a list",
+		}
+	}
+
+	/// Only `false` and `nil` count as false, like in most lisps.
+	pub fn is_truthy(&self) -> bool {
+		!matches!(self, Self::Nil | Self::Boolean(false))
+	}
+}
+
+impl Display for Value {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			// Print atoms the same way they would be written in source
+			Self::Nil => write!(f, "{}", Phrase::Nil),
+			Self::Boolean(boolean) => write!(f, "{}", Phrase::Boolean(*boolean)),
+			Self::Number(number) => write!(f, "{}", number),
+			Self::Text(text) => write!(f, "{}", Phrase::Text(text.clone())),
+			Self::Char(c) => write!(f, "{}", Phrase::Char(*c)),
+			Self::Keyword(keyword) => write!(f, "{}", Phrase::Keyword(keyword.clone())),
+			Self::List(values) => {
+				write!(f, "[")?;
+				for (i, value) in values.iter().enumerate() {
+					if i > 0 {
+						write!(f, " ")?;
+					}
+					write!(f, "{}", value)?;
+				}
+				write!(f, "]")
+			}
+		}
+	}
+}
@@ -0,0 +1,294 @@
+use crate::parser::write_comment;
+use crate::Expression;
+use crate::ExpressionKind;
+use crate::Phrase;
+use crate::Program;
+use crate::Spanned;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FormatOptions {
+	/// How many spaces each level of nesting is indented by.
+	pub indent_width: usize,
+	/// How long a line may get before an `Item` is split across several.
+	pub max_width: usize,
+}
+
+impl Default for FormatOptions {
+	fn default() -> Self {
+		Self {
+			indent_width: 2,
+			max_width: 80,
+		}
+	}
+}
+
+/// Prints a program back out as consistently indented source. The contents of blocks
+/// and lists are placed one per line, while items stay on one line for as long as they
+/// fit.
+pub fn format(program: &Program, options: FormatOptions) -> String {
+	let mut formatter = Formatter {
+		options,
+		output: String::new(),
+	};
+	for expression in program {
+		formatter.expression(expression, 0);
+		formatter.output.push('\n');
+	}
+	for comment in &program.trailing_comments {
+		formatter.comment(comment);
+		formatter.output.push('\n');
+	}
+	formatter.output
+}
+
+struct Formatter {
+	options: FormatOptions,
+	output: String,
+}
+
+impl Formatter {
+	fn column(&self) -> usize {
+		let line = match self.output.rfind('\n') {
+			Some(i) => &self.output[i + 1..],
+			None => &self.output,
+		};
+		line.chars().count()
+	}
+
+	fn fits(&self, text: &str) -> bool {
+		self.column() + text.chars().count() <= self.options.max_width
+	}
+
+	fn new_line(&mut self, depth: usize) {
+		self.output.push('\n');
+		for _ in 0..depth * self.options.indent_width {
+			self.output.push(' ');
+		}
+	}
+
+	fn comment(&mut self, body: &str) {
+		// Writing to a `String` can't fail
+		let _ = write_comment(&mut self.output, body);
+	}
+
+	fn expression(&mut self, expression: &Expression, depth: usize) {
+		for comment in &expression.leading_comments {
+			self.comment(comment);
+			self.new_line(depth);
+		}
+
+		// Breaking an empty expression across lines wouldn't make it any narrower
+		if let Some(flat) =
+			flat(expression).filter(|flat| expression.values.is_empty() || self.fits(flat))
+		{
+			self.output.push_str(&flat);
+			return;
+		}
+
+		self.output.push(expression.kind.initiator());
+		let mut values = expression.values.iter().peekable();
+		let mut previous = None;
+
+		// Items keep as much as they can on their first line, like the name of whatever
+		// is being called, and only break once they run out of room.
+		if expression.kind == ExpressionKind::Item {
+			// Nothing can follow a comment on its line, or it would be part of it
+			let mut commented = false;
+			if let Some(first) = values.next() {
+				self.phrase(first, depth + 1);
+				previous = Some(first);
+				commented = matches!(first.node, Phrase::Comment(_));
+			}
+			while let Some(value) = values.peek().filter(|_| !commented) {
+				match flat_phrase(value) {
+					Some(flat) if self.fits(&format!(" {}", flat)) => {
+						self.output.push(' ');
+						self.output.push_str(&flat);
+						previous = values.next();
+					}
+					_ => break,
+				}
+			}
+		}
+
+		for value in values {
+			// A comment that shared a line with whatever came before it should stay
+			// there, instead of looking like it's about whatever comes next
+			let trailing = matches!(value.node, Phrase::Comment(_))
+				&& previous.is_some_and(|previous: &Spanned<Phrase>| {
+					previous.span.end.line == value.span.start.line
+				});
+			match trailing {
+				true => self.output.push(' '),
+				false => self.new_line(depth + 1),
+			}
+			self.phrase(value, depth + 1);
+			previous = Some(value);
+		}
+
+		let mut ends_with_comment = expression
+			.values
+			.last()
+			.is_some_and(|value| value.is_line_comment());
+		if let Some(tail) = &expression.tail {
+			match flat_phrase(tail)
+				.filter(|flat| !ends_with_comment && self.fits(&format!(" . {}", flat)))
+			{
+				Some(flat) => {
+					self.output.push_str(" . ");
+					self.output.push_str(&flat);
+				}
+				None => {
+					self.new_line(depth + 1);
+					self.output.push_str(". ");
+					self.phrase(tail, depth + 1);
+				}
+			}
+			ends_with_comment = false;
+		}
+		if expression.kind != ExpressionKind::Item || ends_with_comment {
+			self.new_line(depth);
+		}
+		self.output.push(expression.kind.terminator());
+	}
+
+	fn phrase(&mut self, phrase: &Phrase, depth: usize) {
+		match phrase {
+			Phrase::Expression(expression) => self.expression(expression, depth),
+			Phrase::Comment(body) => self.comment(body),
+			phrase => self.output.push_str(&phrase.to_string()),
+		}
+	}
+}
+
+/// Prints `expression` on a single line, if it is allowed to be. Comments, and the
+/// contents of blocks and lists, always need lines of their own.
+fn flat(expression: &Expression) -> Option<String> {
+	if expression.kind != ExpressionKind::Item && !expression.values.is_empty() {
+		return None;
+	}
+
+	let mut output = expression.kind.initiator().to_string();
+	for (i, value) in expression.values.iter().enumerate() {
+		if i > 0 {
+			output.push(' ');
+		}
+		output.push_str(&flat_phrase(value)?);
+	}
+	if let Some(tail) = &expression.tail {
+		output.push_str(" . ");
+		output.push_str(&flat_phrase(tail)?);
+	}
+	output.push(expression.kind.terminator());
+	Some(output)
+}
+
+fn flat_phrase(phrase: &Phrase) -> Option<String> {
+	match phrase {
+		Phrase::Expression(expression) => flat(expression),
+		Phrase::Comment(_) => None,
+		phrase => Some(phrase.to_string()),
+	}
+}
+
+#[cfg(test)]
+mod format_tests {
+	use super::*;
+	use crate::parse;
+
+	#[test]
+	fn nested() {
+		let source = r#"(func add (a b) {(+ a b) (print "hi")}) [1 [2 3] []]"#;
+		assert_eq!(
+			format(&parse(source).unwrap(), FormatOptions::default()),
+			r#"(func add (a b)
+  {
+    (+ a b)
+    (print "hi")
+  })
+[
+  1
+  [
+    2
+    3
+  ]
+  []
+]
+"#
+		);
+	}
+
+	#[test]
+	fn empty() {
+		let source = "[] (a () {})";
+		let options = FormatOptions {
+			indent_width: 2,
+			max_width: 0,
+		};
+		assert_eq!(
+			format(&parse(source).unwrap(), options),
+			"[]\n(a\n  ()\n  {})\n"
+		);
+	}
+
+	#[test]
+	fn long_items() {
+		let source = r#"(println "I have a friend named" (Friend.name friend) "!")"#;
+		let options = FormatOptions {
+			indent_width: 4,
+			max_width: 40,
+		};
+		assert_eq!(
+			format(&parse(source).unwrap(), options),
+			r#"(println "I have a friend named"
+    (Friend.name friend)
+    "!")
+"#
+		);
+	}
+
+	#[test]
+	fn pairs() {
+		let short = "(a  .   b)";
+		assert_eq!(
+			format(&parse(short).unwrap(), FormatOptions::default()),
+			"(a . b)\n"
+		);
+
+		let long = "(first-name . {(print \"hi\")})";
+		assert_eq!(
+			format(&parse(long).unwrap(), FormatOptions::default()),
+			"(first-name\n  . {\n    (print \"hi\")\n  })\n"
+		);
+	}
+
+	#[test]
+	fn only_comments() {
+		let source = "; keep me\n#| and me |#";
+		assert_eq!(
+			format(&parse(source).unwrap(), FormatOptions::default()),
+			"; keep me\n; and me \n"
+		);
+	}
+
+	#[test]
+	fn comments() {
+		let source = "; doc\n(map ; here's a comment\n (hello friend)) ; trailing";
+		assert_eq!(
+			format(&parse(source).unwrap(), FormatOptions::default()),
+			"; doc\n(map ; here's a comment\n  (hello friend))\n; trailing\n"
+		);
+	}
+
+	#[test]
+	fn leading_comment() {
+		for (source, expected) in [
+			("(; c\n def)", "(; c\n  def)\n"),
+			("(#| c |# def)", "(; c \n  def)\n"),
+		] {
+			let formatted = format(&parse(source).unwrap(), FormatOptions::default());
+			assert_eq!(formatted, expected, "{}", source);
+			assert_eq!(parse(&formatted).unwrap()[0].values.len(), 2, "{}", source);
+		}
+	}
+}
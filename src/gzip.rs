@@ -0,0 +1,137 @@
+//! Just enough of gzip to read compressed source files, on top of the raw deflate
+//! streams that `miniz_oxide` can decompress.
+
+use std::io;
+
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+const DEFLATE: u8 = 8;
+
+// Flags for optional fields in the header
+const FHCRC: u8 = 1 << 1;
+const FEXTRA: u8 = 1 << 2;
+const FNAME: u8 = 1 << 3;
+const FCOMMENT: u8 = 1 << 4;
+
+/// Whether `bytes` begin like a gzip file does.
+pub fn is_gzip(bytes: &[u8]) -> bool {
+	bytes.starts_with(&MAGIC)
+}
+
+/// Decompresses a gzip file with a single member, checking it against the length and
+/// checksum it was stored with.
+pub fn decompress(bytes: &[u8]) -> io::Result<Vec<u8>> {
+	if !is_gzip(bytes) {
+		return Err(invalid("not gzip compressed"));
+	}
+	if bytes.len() < 18 {
+		return Err(invalid("gzip file is truncated"));
+	}
+	if bytes[2] != DEFLATE {
+		return Err(invalid("gzip file uses an unknown compression method"));
+	}
+
+	let flags = bytes[3];
+	let (body, trailer) = bytes.split_at(bytes.len() - 8);
+	let mut start = 10;
+	if flags & FEXTRA != 0 {
+		let length = body
+			.get(start..start + 2)
+			.ok_or_else(|| invalid("gzip file is truncated"))?;
+		start += 2 + u16::from_le_bytes([length[0], length[1]]) as usize;
+	}
+	for flag in [FNAME, FCOMMENT] {
+		if flags & flag != 0 {
+			let length = body
+				.get(start..)
+				.and_then(|rest| rest.iter().position(|&b| b == 0))
+				.ok_or_else(|| invalid("gzip file is truncated"))?;
+			start += length + 1;
+		}
+	}
+	if flags & FHCRC != 0 {
+		start += 2;
+	}
+
+	let compressed = body
+		.get(start..)
+		.ok_or_else(|| invalid("gzip file is truncated"))?;
+	let data = miniz_oxide::inflate::decompress_to_vec(compressed)
+		.map_err(|_| invalid("gzip file is corrupt"))?;
+
+	let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+	let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+	if crc32(&data) != crc || data.len() as u32 != size {
+		return Err(invalid("gzip file is corrupt"));
+	}
+	Ok(data)
+}
+
+fn invalid(message: &str) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The CRC-32 checksum gzip stores alongside what it compresses.
+fn crc32(bytes: &[u8]) -> u32 {
+	let mut crc = !0u32;
+	for &byte in bytes {
+		crc ^= byte as u32;
+		for _ in 0..8 {
+			crc = match crc & 1 {
+				1 => (crc >> 1) ^ 0xedb8_8320,
+				_ => crc >> 1,
+			};
+		}
+	}
+	!crc
+}
+
+#[cfg(test)]
+mod decompress_tests {
+	use super::*;
+
+	fn compress(data: &[u8]) -> Vec<u8> {
+		let mut bytes = vec![0x1f, 0x8b, DEFLATE, FNAME, 0, 0, 0, 0, 0, 0xff];
+		bytes.extend(b"program.yall\0");
+		bytes.extend(miniz_oxide::deflate::compress_to_vec(data, 6));
+		bytes.extend(crc32(data).to_le_bytes());
+		bytes.extend((data.len() as u32).to_le_bytes());
+		bytes
+	}
+
+	#[test]
+	fn round_trip() {
+		let source = "(func add (a b) (+ a b))\n";
+		let compressed = compress(source.as_bytes());
+		assert!(is_gzip(&compressed));
+
+		let decompressed = String::from_utf8(decompress(&compressed).unwrap()).unwrap();
+		assert_eq!(decompressed, source);
+		assert_eq!(
+			yall::parse(&decompressed).unwrap(),
+			yall::parse(source).unwrap()
+		);
+	}
+
+	#[test]
+	fn crc() {
+		assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+	}
+
+	#[test]
+	fn not_gzip() {
+		let error = decompress(b"(a b c)").unwrap_err();
+		assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+		assert_eq!(error.to_string(), "not gzip compressed");
+	}
+
+	#[test]
+	fn corrupt() {
+		let mut compressed = compress(b"(a b c)");
+		let last = compressed.len() - 1;
+		compressed[last] ^= 1;
+		assert_eq!(
+			decompress(&compressed).unwrap_err().to_string(),
+			"gzip file is corrupt"
+		);
+	}
+}
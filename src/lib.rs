@@ -0,0 +1,397 @@
+mod diagnostic;
+pub mod eval;
+mod format;
+mod lint;
+pub mod parser;
+mod util {
+	pub mod peek_while;
+}
+pub mod visit;
+
+pub use diagnostic::parse_diagnostics;
+pub use diagnostic::Diagnostic;
+pub use diagnostic::Position;
+pub use diagnostic::Related;
+pub use diagnostic::Severity;
+pub use format::format;
+pub use format::FormatOptions;
+pub use lint::lint_duplicate_keys;
+pub use parser::group_doc_comments;
+pub use parser::structural_hash;
+pub use parser::tokenize;
+pub use parser::BorrowedExpression;
+pub use parser::BorrowedPhrase;
+pub use parser::BorrowedProgram;
+pub use parser::Expression;
+pub use parser::ExpressionKind;
+pub use parser::Expressions;
+pub use parser::Interner;
+pub use parser::LosslessNode;
+pub use parser::LosslessProgram;
+pub use parser::Number;
+pub use parser::ParseError;
+pub use parser::ParseResult;
+pub use parser::ParsedProgram;
+pub use parser::Phrase;
+pub use parser::PhraseKind;
+pub use parser::Program;
+pub use parser::QuoteKind;
+pub use parser::ReadChars;
+pub use parser::Span;
+pub use parser::Spanned;
+pub use parser::Symbol;
+pub use parser::Token;
+pub use parser::TokenKind;
+pub use visit::Visitor;
+
+/// Parses a complete program from `source`.
+pub fn parse(source: &str) -> Result<Program, ParseError> {
+	parser::parse_tokens(source)
+}
+
+/// Parses a complete program from `bytes`, which have to be valid UTF-8.
+pub fn parse_bytes(bytes: &[u8]) -> Result<Program, ParseError> {
+	let source =
+		std::str::from_utf8(bytes).map_err(|error| ParseError::invalid_utf8(bytes, error))?;
+	parse(source)
+}
+
+/// Parses only the first top-level expression of `source`, returning it along with the
+/// byte offset just past its end, so that parsing can pick up again from there. Nothing
+/// after the expression is looked at, even if it wouldn't parse.
+pub fn parse_one(source: &str) -> Result<(Expression, usize), ParseError> {
+	let mut parser = parser::Parser::from(source);
+	let expression = parser::parse_expression(&mut parser)?;
+	Ok((expression, parser.offset()))
+}
+
+/// Parses a complete program from `source` without recursing, so that there's no limit
+/// on how deeply expressions may be nested. Comparing, cloning, or printing the result
+/// still recurses though, so those would need the same care.
+pub fn parse_unbounded(source: &str) -> Result<Program, ParseError> {
+	parser::parse_tokens_unbounded(source)
+}
+
+/// Parses a complete program from `source`, borrowing from it rather than copying
+/// wherever possible.
+pub fn parse_borrowed(source: &str) -> Result<BorrowedProgram<'_>, ParseError> {
+	parser::parse_borrowed_program(&mut source.into())
+}
+
+/// Parses a complete program from `source`, taking ownership of it so that the program
+/// can hand back the text of any of its nodes.
+pub fn parse_owned(source: String) -> Result<ParsedProgram, ParseError> {
+	parser::parse_owned_program(source)
+}
+
+/// Parses a complete program from `reader` as it's read, without reading all of it into
+/// memory first.
+pub fn parse_reader<R: std::io::Read>(reader: R) -> Result<Program, ParseError> {
+	parser::parse_read_program(std::io::BufReader::new(reader))
+}
+
+/// Parses as much of a program from `source` as possible, collecting every error
+/// instead of stopping at the first.
+pub fn parse_with_recovery(source: &str) -> ParseResult {
+	parser::parse_program_with_recovery(&mut source.into())
+}
+
+/// Parses a complete program from `source`, keeping all of its whitespace, so that it
+/// can be printed back out byte for byte with [`reprint`].
+pub fn parse_lossless(source: &str) -> Result<LosslessProgram, ParseError> {
+	parser::parse_lossless_program(source)
+}
+
+/// Prints a program parsed with [`parse_lossless`] back out exactly as it was written.
+pub fn reprint(program: &LosslessProgram) -> String {
+	program.to_string()
+}
+
+/// How many nodes make up a whole program, as counted by [`Expression::node_count`].
+pub fn node_count(program: &[Expression]) -> usize {
+	program.iter().map(Expression::node_count).sum()
+}
+
+/// Finds every item in a program, at any depth, that begins with the identifier `name`,
+/// like every `(def ...)`, in the order they appear. Comments before the identifier are
+/// skipped over.
+pub fn find_items<'a>(
+	program: &'a [Expression],
+	name: &'a str,
+) -> impl Iterator<Item = &'a Expression> + 'a {
+	// Walked with a stack rather than by recursing, so that deep programs are fine too
+	let mut stack = program.iter().rev().collect::<Vec<_>>();
+	std::iter::from_fn(move || {
+		while let Some(expression) = stack.pop() {
+			stack.extend(expression.iter().rev().filter_map(Phrase::as_expression));
+
+			let head = expression
+				.values
+				.iter()
+				.find(|value| !matches!(value.node, Phrase::Comment(_)));
+			let named = head.and_then(|head| head.node.as_identifier()) == Some(name);
+			if expression.kind == ExpressionKind::Item && named {
+				return Some(expression);
+			}
+		}
+		None
+	})
+}
+
+/// Prints a program back out as source, with each top-level expression, and each comment
+/// after the last of them, on its own line.
+pub fn print(program: &Program) -> String {
+	program.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_item() {
+		let program = parse("(add 1 2)").unwrap();
+		assert_eq!(program.len(), 1);
+		assert_eq!(program[0].kind, ExpressionKind::Item);
+		assert_eq!(
+			program[0]
+				.values
+				.iter()
+				.map(|value| &value.node)
+				.collect::<Vec<_>>(),
+			vec![
+				&Phrase::Identifier("add".to_string()),
+				&Phrase::Number(Number::Integer(1)),
+				&Phrase::Number(Number::Integer(2)),
+			]
+		);
+	}
+
+	#[test]
+	fn parse_utf8_bytes() {
+		let source = "(greet \"🦊\")";
+		assert_eq!(parse_bytes(source.as_bytes()), parse(source));
+
+		// The fox is cut off partway through
+		let bytes = "(a)\n(greet \"🦊\")".as_bytes();
+		let error = parse_bytes(&bytes[..bytes.len() - 4]).unwrap_err();
+		assert_eq!(
+			error,
+			ParseError::InvalidUtf8 {
+				location: parser::Location {
+					line: 1,
+					column: 8,
+					offset: 12,
+				},
+			}
+		);
+		assert_eq!(
+			error.to_string(),
+			"source isn't valid UTF-8, starting at byte 12"
+		);
+		assert!(matches!(
+			parse_bytes(b"(a \xff)"),
+			Err(ParseError::InvalidUtf8 { location }) if location.offset == 3
+		));
+	}
+
+	#[test]
+	fn parse_one_at_a_time() {
+		let source = "(a)(b)";
+		let (expression, offset) = parse_one(source).unwrap();
+		assert_eq!(expression.to_string(), "(a)");
+		assert_eq!(offset, 3);
+		assert_eq!(&source[offset..], "(b)");
+
+		let (expression, offset) = parse_one(&source[offset..]).unwrap();
+		assert_eq!(expression.to_string(), "(b)");
+		assert_eq!(offset, 3);
+
+		// Whatever follows isn't looked at
+		assert_eq!(parse_one("; doc\n(a) ]").unwrap().1, 9);
+		assert!(parse_one("").is_err());
+	}
+
+	#[test]
+	fn print_round_trip() {
+		let source = r#"
+			; a comment
+			(func add::i32 (a::i32 b::i32) ; another comment
+				(+ a b))
+
+			#| a comment
+   across lines |#
+			{hey "there\n\"friend\"" 'x' '\''}
+			[1 -2.5 0x10 1e100 true false nil :label]
+		"#;
+
+		let printed = print(&parse(source).unwrap());
+		assert_eq!(
+			printed,
+			r#"; a comment
+(func add (a b) ; another comment
+(+ a b))
+#| a comment
+   across lines |#
+{hey "there\n\"friend\"" 'x' '\''}
+[1 -2.5 16 1e100 true false nil :label]
+"#
+		);
+
+		// Printing something that was already printed should change nothing, including
+		// where every node ends up.
+		let reparsed = parse(&printed).unwrap();
+		assert_eq!(print(&reparsed), printed);
+		assert_eq!(parse(&print(&reparsed)).unwrap(), reparsed);
+	}
+
+	#[test]
+	fn reprint_round_trip() {
+		let source = "  ( a\t'b ; note\r\n  [c::int] )  \n";
+		assert_eq!(reprint(&parse_lossless(source).unwrap()), source);
+	}
+
+	#[test]
+	fn program_node_count() {
+		assert_eq!(node_count(&parse("(a b) [c (d)]").unwrap()), 7);
+		assert_eq!(node_count(&[]), 0);
+	}
+
+	#[test]
+	fn find_defs() {
+		let source = "(def a 1) (func f ()\n (def b 2)\n [(a) (; c\n def)]) (define c) [def]";
+		let program = parse(source).unwrap();
+		let defs = find_items(&program, "def")
+			.map(|expression| expression.to_string())
+			.collect::<Vec<_>>();
+		assert_eq!(defs, ["(def a 1)", "(def b 2)", "(; c\ndef)"]);
+		assert_eq!(find_items(&program, "undefined").count(), 0);
+	}
+
+	#[test]
+	fn parse_is_comparable() {
+		let source = "(map [1 2.5 \"three\"] {four})";
+		assert_eq!(parse(source).unwrap(), parse(source).unwrap());
+		assert_ne!(parse(source).unwrap(), parse("(map)").unwrap());
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn serialize_json() {
+		let program = parse("[a 1 nil]").unwrap();
+		assert_eq!(
+			serde_json::to_value(&program.expressions).unwrap(),
+			serde_json::json!([{
+				"kind": "List",
+				"values": [
+					{
+						"type": "Identifier",
+						"value": "a",
+						"span": {
+							"start": { "line": 0, "column": 1, "offset": 1 },
+							"end": { "line": 0, "column": 2, "offset": 2 },
+						},
+					},
+					{
+						"type": "Number",
+						"value": 1,
+						"span": {
+							"start": { "line": 0, "column": 3, "offset": 3 },
+							"end": { "line": 0, "column": 4, "offset": 4 },
+						},
+					},
+					{
+						"type": "Nil",
+						"span": {
+							"start": { "line": 0, "column": 5, "offset": 5 },
+							"end": { "line": 0, "column": 8, "offset": 8 },
+						},
+					},
+				],
+				"leading_comments": [],
+				"span": {
+					"start": { "line": 0, "column": 0, "offset": 0 },
+					"end": { "line": 0, "column": 9, "offset": 9 },
+				},
+			}])
+		);
+
+		let program = parse("(a) ; b").unwrap();
+		assert_eq!(
+			serde_json::to_value(&program).unwrap()["trailing_comments"],
+			serde_json::json!([" b"])
+		);
+	}
+
+	#[test]
+	fn parse_byte_order_mark() {
+		let program = parse("\u{FEFF}(x)").unwrap();
+		assert_eq!(print(&program), print(&parse("(x)").unwrap()));
+		// Columns don't count the byte order mark, but byte offsets still do
+		assert_eq!(program[0].span.start.column, 0);
+		assert_eq!(program[0].span.start.offset, 3);
+	}
+
+	#[test]
+	fn parse_unbalanced() {
+		assert!(parse("(add 1 2").is_err());
+	}
+
+	#[test]
+	fn parse_never_panics() {
+		let source = r#"; comment
+			#| block #| nested |# |#
+			(func add (a b) (+ a b))
+			{hey "there\n\u{1F600}" 'x' '\'' :label}
+			[1 -2.5 0x1f 0o17 0b101 1e100 .5 true false nil]
+			(a::int b.c -1 +2 <= !=)
+		"#;
+		let garbage = [
+			"(",
+			")",
+			"]",
+			"}",
+			"(]",
+			"[)",
+			"{)",
+			"\"",
+			"'",
+			"''",
+			"'ab'",
+			"'\\",
+			";",
+			"#",
+			"#|",
+			"#| #|",
+			"|#",
+			"::",
+			"a::",
+			"0x",
+			"0b2",
+			"0o9",
+			"1e",
+			"1.2.3",
+			"-",
+			"+",
+			".",
+			"..",
+			"\"\\u{",
+			"\"\\u{}\"",
+			"\"\\u{110000}\"",
+			"\"\\q\"",
+			"\0",
+			"\u{7f}",
+			"(\u{301})",
+		];
+
+		// Every prefix of a valid program is a good source of half-finished input.
+		let prefixes = source
+			.char_indices()
+			.map(|(i, _)| &source[..i])
+			.chain(garbage);
+		for input in prefixes {
+			let result = std::panic::catch_unwind(|| parse(input));
+			assert!(result.is_ok(), "parsing {:?} panicked", input);
+		}
+	}
+}
@@ -0,0 +1,99 @@
+//! Checks for programs that parse fine, but probably don't mean what they say.
+
+use std::collections::HashMap;
+
+use crate::visit::walk;
+use crate::visit::walk_expression;
+use crate::Diagnostic;
+use crate::Expression;
+use crate::ExpressionKind;
+use crate::Phrase;
+use crate::Related;
+use crate::Severity;
+use crate::Span;
+use crate::Visitor;
+
+/// Finds keys written more than once in the same block, treating blocks as records of
+/// alternating keys and values, like `{name "friend" age 4}`. Only identifiers count as
+/// keys, and comments are skipped over when pairing keys with their values. Each repeat
+/// is reported where it's written again, pointing back to where the key first was.
+pub fn lint_duplicate_keys(program: &[Expression]) -> Vec<Diagnostic> {
+	let mut lint = DuplicateKeys::default();
+	walk(&mut lint, program);
+	lint.diagnostics
+}
+
+#[derive(Default)]
+struct DuplicateKeys {
+	diagnostics: Vec<Diagnostic>,
+}
+
+impl Visitor for DuplicateKeys {
+	fn visit_expression(&mut self, expression: &Expression) {
+		if expression.kind == ExpressionKind::Block {
+			let mut keys: HashMap<&str, Span> = HashMap::new();
+			let entries = expression
+				.values
+				.iter()
+				.filter(|value| !matches!(value.node, Phrase::Comment(_)));
+			for key in entries.step_by(2) {
+				let Some(name) = key.node.as_identifier() else {
+					continue;
+				};
+				match keys.get(name) {
+					Some(first) => self.diagnostics.push(Diagnostic {
+						severity: Severity::Warning,
+						message: format!("duplicate key {}", name),
+						start: key.span.start.into(),
+						end: key.span.end.into(),
+						related: vec![Related {
+							message: format!("{} was first used here", name),
+							start: first.start.into(),
+							end: first.end.into(),
+						}],
+					}),
+					None => {
+						keys.insert(name, key.span);
+					}
+				}
+			}
+		}
+
+		walk_expression(self, expression);
+	}
+}
+
+#[cfg(test)]
+mod lint_duplicate_keys_tests {
+	use super::*;
+	use crate::parse;
+	use crate::Position;
+
+	#[test]
+	fn duplicate() {
+		let diagnostics = lint_duplicate_keys(&parse("{ a 1 a 2 }").unwrap());
+		assert_eq!(
+			diagnostics,
+			vec![Diagnostic {
+				severity: Severity::Warning,
+				message: "duplicate key a".to_string(),
+				start: Position { row: 0, col: 6 },
+				end: Position { row: 0, col: 7 },
+				related: vec![Related {
+					message: "a was first used here".to_string(),
+					start: Position { row: 0, col: 2 },
+					end: Position { row: 0, col: 3 },
+				}],
+			}]
+		);
+	}
+
+	#[test]
+	fn only_keys() {
+		// Values can repeat, and so can keys of different blocks, or of anything else
+		let source = "{a b c b} {a 1} (x x) [y y] {; the key\n d a\n e {d 1 d 2}}";
+		let diagnostics = lint_duplicate_keys(&parse(source).unwrap());
+		assert_eq!(diagnostics.len(), 1);
+		assert_eq!(diagnostics[0].start, Position { row: 2, col: 8 });
+	}
+}
@@ -1,24 +1,147 @@
 use miette::miette;
 use std::env;
 use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::ExitCode;
+use yall::parser;
+use yall::Diagnostic;
+use yall::Program;
 
+#[cfg(feature = "gzip")]
+mod gzip;
 mod options;
-mod parser;
-mod util {
-	pub mod peek_while;
-}
+mod repl;
+mod stats;
 
+use options::Action;
+use options::Emit;
+use options::Input;
 use options::Options;
 
-fn main() -> miette::Result<()> {
-	let options = env::args().skip(1).collect::<Options>();
+fn main() -> ExitCode {
+	let options = match env::args().skip(1).collect::<Action>() {
+		Action::Run(options) => options,
+		Action::Help => {
+			println!("{}", options::USAGE);
+			return ExitCode::SUCCESS;
+		}
+		Action::Version => {
+			println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+			return ExitCode::SUCCESS;
+		}
+		Action::Invalid(message) => {
+			eprintln!("{}\n\n{}", message, options::USAGE);
+			return ExitCode::FAILURE;
+		}
+	};
+
+	if options.repl {
+		return match repl::run(&options) {
+			Ok(()) => ExitCode::SUCCESS,
+			Err(error) => {
+				eprintln!("{}", error);
+				ExitCode::FAILURE
+			}
+		};
+	}
 
-	let source = fs::read_to_string(options.input).or(Err(miette!("failed to read input file")))?;
-	let program = parser::parse_program(&mut (&source).into())?;
+	// Keep going after a file fails, so that every file gets checked
+	let mut failed = false;
+	let mut emitted = String::new();
+	for input in &options.inputs {
+		match run(input, &options) {
+			Ok(output) if options.output.is_none() => print!("{}", output),
+			Ok(output) => emitted.push_str(&output),
+			Err(report) => {
+				eprintln!("{}: {}", input, report);
+				failed = true;
+			}
+		}
+	}
 
-	if options.debug_parser {
-		println!("{:#?}", program);
+	if let Some(path) = &options.output {
+		if let Err(error) = write_output(path, &emitted) {
+			eprintln!("failed to write {}: {}", path.display(), error);
+			failed = true;
+		}
 	}
 
-	Ok(())
+	match failed {
+		true => ExitCode::FAILURE,
+		false => ExitCode::SUCCESS,
+	}
+}
+
+fn write_output(path: &Path, output: &str) -> Result<(), String> {
+	fs::write(path, output).map_err(|error| match path.parent() {
+		Some(directory) if error.kind() == io::ErrorKind::NotFound => {
+			format!("directory {} doesn't exist", directory.display())
+		}
+		_ => error.to_string(),
+	})
+}
+
+/// Parses `input`, returning whatever should be emitted for it.
+fn run(input: &Input, options: &Options) -> miette::Result<String> {
+	let source = input
+		.read()
+		.map_err(|error| miette!("failed to read input: {}", error))?;
+	let mut parser = options.parser(&source);
+
+	if options.emit == Some(Emit::Diagnostics) {
+		let errors = parser::parse_program_with_recovery(&mut parser).errors;
+		return emit_diagnostics(&errors.iter().map(Diagnostic::from).collect::<Vec<_>>());
+	}
+	let program =
+		parser::parse_program(&mut parser).map_err(|error| miette!("{}", error.render(&source)))?;
+
+	if options.check {
+		return Ok(String::new());
+	}
+
+	if options.stats {
+		return Ok(stats::Stats::collect(&program).to_string());
+	}
+
+	let output = match options.emit {
+		Some(Emit::Debug) => format!("{:?}\n", program),
+		Some(Emit::Pretty) => format!("{:#?}\n", program),
+		Some(Emit::Sexpr) => yall::print(&program),
+		Some(Emit::Json) => emit_json(&program)?,
+		// Handled before parsing, since errors don't stop it
+		Some(Emit::Diagnostics) => unreachable!(),
+		None if options.debug_parser => format!("{:#?}\n", program),
+		None => String::new(),
+	};
+
+	Ok(output)
+}
+
+#[cfg(feature = "serde")]
+fn emit_json(program: &Program) -> miette::Result<String> {
+	let json =
+		serde_json::to_string_pretty(program).or(Err(miette!("failed to serialize program")))?;
+	Ok(format!("{}\n", json))
+}
+
+#[cfg(not(feature = "serde"))]
+fn emit_json(_: &Program) -> miette::Result<String> {
+	Err(miette!(
+		"emitting json requires yall to be built with the serde feature"
+	))
+}
+
+#[cfg(feature = "serde")]
+fn emit_diagnostics(diagnostics: &[Diagnostic]) -> miette::Result<String> {
+	let json = serde_json::to_string_pretty(diagnostics)
+		.or(Err(miette!("failed to serialize diagnostics")))?;
+	Ok(format!("{}\n", json))
+}
+
+#[cfg(not(feature = "serde"))]
+fn emit_diagnostics(_: &[Diagnostic]) -> miette::Result<String> {
+	Err(miette!(
+		"emitting diagnostics requires yall to be built with the serde feature"
+	))
 }
@@ -1,28 +1,338 @@
+use std::fmt;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::io::Read;
+use std::path::Path;
 use std::path::PathBuf;
-use std::process::exit;
+use std::str::Chars;
+use std::str::FromStr;
+use yall::parser::Parser;
+use yall::parser::ALL_BRACKETS;
+use yall::parser::DEFAULT_COMMENT_PREFIX;
+use yall::parser::DEFAULT_MAX_DEPTH;
+use yall::parser::DEFAULT_MAX_ERRORS;
+use yall::ExpressionKind;
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Emit {
+	/// The parsed program's `Debug` representation.
+	Debug,
+	/// The parsed program's `Debug` representation, spread across lines.
+	Pretty,
+	/// The parsed program printed back out as source.
+	Sexpr,
+	Json,
+	/// Every error found while recovering from them, as JSON, instead of the program.
+	Diagnostics,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum Input {
+	#[default]
+	Stdin,
+	File(PathBuf),
+}
+
+impl Input {
+	pub fn read(&self) -> io::Result<String> {
+		match self {
+			Input::Stdin => {
+				let mut source = String::new();
+				io::stdin().read_to_string(&mut source)?;
+				Ok(source)
+			}
+			Input::File(path) => read_file(path),
+		}
+	}
+}
+
+#[cfg(not(feature = "gzip"))]
+fn read_file(path: &Path) -> io::Result<String> {
+	fs::read_to_string(path)
+}
+
+/// Reads the file at `path`, decompressing it first if it's gzipped, either because it
+/// starts like a gzip file does, or because its name ends in `.gz`.
+#[cfg(feature = "gzip")]
+fn read_file(path: &Path) -> io::Result<String> {
+	let mut bytes = fs::read(path)?;
+	if crate::gzip::is_gzip(&bytes) || path.extension().is_some_and(|extension| extension == "gz") {
+		bytes = crate::gzip::decompress(&bytes)?;
+	}
+	String::from_utf8(bytes).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+impl Display for Input {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Input::Stdin => write!(f, "<stdin>"),
+			Input::File(path) => write!(f, "{}", path.display()),
+		}
+	}
+}
+
+impl From<&str> for Input {
+	fn from(arg: &str) -> Self {
+		match arg {
+			"-" => Input::Stdin,
+			path => Input::File(PathBuf::from(path)),
+		}
+	}
+}
+
+/// Builds up `Options` one setting at a time, such as from the command line.
 #[derive(Clone, Debug, Default)]
-struct OptionsBuilder {
+pub struct OptionsBuilder {
 	debug_parser: bool,
-	input: Option<PathBuf>,
+	emit: Option<Emit>,
+	max_depth: Option<usize>,
+	max_nodes: Option<usize>,
+	max_string_len: Option<usize>,
+	max_identifier_len: Option<usize>,
+	max_errors: Option<usize>,
+	brackets: Option<Vec<ExpressionKind>>,
+	comment_prefix: Option<String>,
+	discard_comments: bool,
+	tab_width: Option<u32>,
+	repl: bool,
+	stats: bool,
+	check: bool,
+	output: Option<PathBuf>,
+	inputs: Vec<Input>,
+}
+
+impl OptionsBuilder {
+	pub fn debug_parser(mut self, debug_parser: bool) -> Self {
+		self.debug_parser = debug_parser;
+		self
+	}
+
+	pub fn emit(mut self, emit: Emit) -> Self {
+		self.emit = Some(emit);
+		self
+	}
+
+	pub fn max_depth(mut self, max_depth: usize) -> Self {
+		self.max_depth = Some(max_depth);
+		self
+	}
+
+	pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+		self.max_nodes = Some(max_nodes);
+		self
+	}
+
+	pub fn max_string_len(mut self, max_string_len: usize) -> Self {
+		self.max_string_len = Some(max_string_len);
+		self
+	}
+
+	pub fn max_identifier_len(mut self, max_identifier_len: usize) -> Self {
+		self.max_identifier_len = Some(max_identifier_len);
+		self
+	}
+
+	pub fn max_errors(mut self, max_errors: usize) -> Self {
+		self.max_errors = Some(max_errors);
+		self
+	}
+
+	pub fn brackets(mut self, brackets: Vec<ExpressionKind>) -> Self {
+		self.brackets = Some(brackets);
+		self
+	}
+
+	pub fn comment_prefix(mut self, comment_prefix: impl Into<String>) -> Self {
+		self.comment_prefix = Some(comment_prefix.into());
+		self
+	}
+
+	pub fn discard_comments(mut self, discard_comments: bool) -> Self {
+		self.discard_comments = discard_comments;
+		self
+	}
+
+	pub fn tab_width(mut self, tab_width: u32) -> Self {
+		self.tab_width = Some(tab_width);
+		self
+	}
+
+	pub fn repl(mut self, repl: bool) -> Self {
+		self.repl = repl;
+		self
+	}
+
+	pub fn stats(mut self, stats: bool) -> Self {
+		self.stats = stats;
+		self
+	}
+
+	pub fn check(mut self, check: bool) -> Self {
+		self.check = check;
+		self
+	}
+
+	pub fn output(mut self, output: impl Into<PathBuf>) -> Self {
+		self.output = Some(output.into());
+		self
+	}
+
+	/// Adds another input to read from, after any already added.
+	pub fn input(mut self, input: impl Into<Input>) -> Self {
+		self.inputs.push(input.into());
+		self
+	}
+
+	/// Checks that the options make sense together, filling in defaults for anything
+	/// that wasn't set.
+	pub fn build(self) -> Result<Options, String> {
+		if self
+			.inputs
+			.iter()
+			.any(|input| matches!(input, Input::File(path) if path.as_os_str().is_empty()))
+		{
+			return Err("input paths can't be empty".to_string());
+		}
+		if matches!(&self.output, Some(path) if path.as_os_str().is_empty()) {
+			return Err("output path can't be empty".to_string());
+		}
+		// A prefix which could be part of the layout of a program would swallow it
+		if let Some(prefix) = &self.comment_prefix {
+			if prefix
+				.chars()
+				.any(|c| c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '{' | '}' | '"'))
+			{
+				return Err(format!("invalid comment prefix: {}", prefix));
+			}
+		}
+
+		Ok(Options {
+			debug_parser: self.debug_parser,
+			emit: self.emit,
+			max_depth: self.max_depth.unwrap_or(DEFAULT_MAX_DEPTH),
+			max_nodes: self.max_nodes,
+			max_string_len: self.max_string_len,
+			max_identifier_len: self.max_identifier_len,
+			max_errors: self.max_errors.unwrap_or(DEFAULT_MAX_ERRORS),
+			brackets: self.brackets.unwrap_or_else(|| ALL_BRACKETS.to_vec()),
+			comment_prefix: self
+				.comment_prefix
+				.unwrap_or_else(|| DEFAULT_COMMENT_PREFIX.to_string()),
+			discard_comments: self.discard_comments,
+			tab_width: self.tab_width.unwrap_or(1),
+			repl: self.repl,
+			stats: self.stats,
+			check: self.check,
+			output: self.output,
+			inputs: match self.inputs.is_empty() {
+				true => vec![Input::Stdin],
+				false => self.inputs,
+			},
+		})
+	}
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Options {
 	pub debug_parser: bool,
-	pub input: PathBuf,
+	pub emit: Option<Emit>,
+	pub max_depth: usize,
+	/// How many nodes a program may have, if there's any limit at all.
+	pub max_nodes: Option<usize>,
+	/// How many bytes long a single string may be, if there's any limit at all.
+	pub max_string_len: Option<usize>,
+	/// How many bytes long a single identifier or keyword may be, if there's any limit.
+	pub max_identifier_len: Option<usize>,
+	/// How many errors to report when emitting diagnostics, before giving up.
+	pub max_errors: usize,
+	/// The kinds of expressions which may be opened, by their brackets.
+	pub brackets: Vec<ExpressionKind>,
+	/// What begins a line comment.
+	pub comment_prefix: String,
+	/// Leave comments out of the parsed program entirely.
+	pub discard_comments: bool,
+	/// How many columns apart tab stops are, when reporting where errors are.
+	pub tab_width: u32,
+	/// Read and evaluate forms from stdin interactively, instead of reading inputs.
+	pub repl: bool,
+	/// Print counts of what each program is made of, instead of the program itself.
+	pub stats: bool,
+	/// Only check that each program parses, without printing anything but errors.
+	pub check: bool,
+	/// Where to write whatever is emitted, instead of stdout.
+	pub output: Option<PathBuf>,
+	pub inputs: Vec<Input>,
 }
 
-impl From<OptionsBuilder> for Options {
-	fn from(builder: OptionsBuilder) -> Self {
-		Options {
-			debug_parser: builder.debug_parser,
-			input: builder.input.expect("no input provided"),
+impl Options {
+	pub fn builder() -> OptionsBuilder {
+		OptionsBuilder::default()
+	}
+
+	/// A parser for `source`, with every limit and setting these options ask for.
+	pub fn parser<'a>(&self, source: &'a str) -> Parser<Chars<'a>> {
+		let mut parser = Parser::from(source)
+			.with_max_depth(self.max_depth)
+			.with_max_errors(self.max_errors)
+			.with_brackets(&self.brackets)
+			.with_comment_prefix(&self.comment_prefix)
+			.with_discard_comments(self.discard_comments)
+			.with_tab_width(self.tab_width);
+		if let Some(max_nodes) = self.max_nodes {
+			parser = parser.with_max_nodes(max_nodes);
+		}
+		if let Some(max_string_len) = self.max_string_len {
+			parser = parser.with_max_string_len(max_string_len);
 		}
+		if let Some(max_identifier_len) = self.max_identifier_len {
+			parser = parser.with_max_identifier_len(max_identifier_len);
+		}
+		parser
 	}
 }
 
-impl<S> FromIterator<S> for Options
+pub const USAGE: &str = "\
+usage: yall [options] [files...]
+
+Reads from stdin when no files are given, or when a file is `-`.
+
+options:
+  -p, --debug-parser    print the parsed program
+  --emit <format>       print the parsed program as the given format
+                        (debug, pretty, sexpr, json, diagnostics)
+  --max-depth <n>       how deeply expressions may be nested (default 256)
+  --max-nodes <n>       how many nodes a program may have (default unlimited)
+  --max-string-len <n>  how many bytes long a string may be (default unlimited)
+  --max-identifier-len <n>
+                        how many bytes long an identifier may be
+                        (default unlimited)
+  --max-errors <n>      how many errors to report with `--emit diagnostics`
+                        before giving up (default 20)
+  --brackets <list>     which brackets may open expressions, like `([`
+                        (default `([{`)
+  --comment <prefix>    what begins a line comment, like `#` or `//`
+                        (default `;`)
+  --discard-comments    leave comments out of the parsed program
+  --tab-width <n>       how many columns apart tab stops are (default 1)
+  -o, --output <file>   write what is printed to a file instead of stdout
+  --stats               print counts of what the program is made of
+  --check               only check that the program parses, printing nothing
+                        but errors
+  --repl                evaluate forms from stdin interactively
+  -h, --help            print this message
+  -V, --version         print the version of yall";
+
+/// What the command line asked for.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Action {
+	Run(Options),
+	Help,
+	Version,
+	Invalid(String),
+}
+
+impl<S> FromIterator<S> for Action
 where
 	S: AsRef<str>,
 {
@@ -30,7 +340,7 @@ where
 	where
 		I: IntoIterator<Item = S>,
 	{
-		let mut options = OptionsBuilder::default();
+		let mut options = Options::builder();
 		let mut args = args.into_iter();
 
 		while let Some(arg) = args.next() {
@@ -38,26 +348,150 @@ where
 			if (arg.len() >= 2 && arg.starts_with('-')) || arg.len() >= 3 && arg.starts_with("--") {
 				match arg {
 					"-p" | "-debug-parser" | "--debug-parser" => {
-						options.debug_parser = true;
+						options = options.debug_parser(true);
+					}
+					"--emit" => {
+						let emit = match args.next().as_ref().map(AsRef::as_ref) {
+							Some("debug") => Emit::Debug,
+							Some("pretty") => Emit::Pretty,
+							Some("sexpr") => Emit::Sexpr,
+							Some("json") => Emit::Json,
+							Some("diagnostics") => Emit::Diagnostics,
+							Some(format) => {
+								return Action::Invalid(format!("unknown emit format: {}", format));
+							}
+							None => return Action::Invalid("--emit requires a format".to_string()),
+						};
+						options = options.emit(emit);
+					}
+					"--max-depth" => {
+						let max_depth = match number_arg(&mut args, arg, "max depth", |_| true) {
+							Ok(max_depth) => max_depth,
+							Err(message) => return Action::Invalid(message),
+						};
+						options = options.max_depth(max_depth);
+					}
+					"--max-nodes" => {
+						let max_nodes = match number_arg(&mut args, arg, "max nodes", |_| true) {
+							Ok(max_nodes) => max_nodes,
+							Err(message) => return Action::Invalid(message),
+						};
+						options = options.max_nodes(max_nodes);
+					}
+					"--max-string-len" => {
+						let max_string_len =
+							match number_arg(&mut args, arg, "max string length", |_| true) {
+								Ok(max_string_len) => max_string_len,
+								Err(message) => return Action::Invalid(message),
+							};
+						options = options.max_string_len(max_string_len);
+					}
+					"--max-identifier-len" => {
+						let max_identifier_len =
+							match number_arg(&mut args, arg, "max identifier length", |_| true) {
+								Ok(max_identifier_len) => max_identifier_len,
+								Err(message) => return Action::Invalid(message),
+							};
+						options = options.max_identifier_len(max_identifier_len);
+					}
+					"--max-errors" => {
+						let max_errors =
+							match number_arg(&mut args, arg, "max errors", |&n: &usize| n > 0) {
+								Ok(max_errors) => max_errors,
+								Err(message) => return Action::Invalid(message),
+							};
+						options = options.max_errors(max_errors);
+					}
+					"--brackets" => {
+						let brackets = match args.next().as_ref().map(AsRef::as_ref) {
+							Some(list) => match list
+								.chars()
+								.map(ExpressionKind::from_initiator)
+								.collect::<Option<Vec<_>>>()
+							{
+								Some(brackets) if !brackets.is_empty() => brackets,
+								_ => {
+									return Action::Invalid(format!("invalid brackets: {}", list));
+								}
+							},
+							None => {
+								return Action::Invalid("--brackets requires a list".to_string())
+							}
+						};
+						options = options.brackets(brackets);
+					}
+					"--comment" => {
+						let prefix = match args.next() {
+							Some(prefix) => prefix.as_ref().to_string(),
+							None => {
+								return Action::Invalid("--comment requires a prefix".to_string())
+							}
+						};
+						options = options.comment_prefix(prefix);
+					}
+					"--tab-width" => {
+						let tab_width =
+							match number_arg(&mut args, arg, "tab width", |&n: &u32| n > 0) {
+								Ok(tab_width) => tab_width,
+								Err(message) => return Action::Invalid(message),
+							};
+						options = options.tab_width(tab_width);
+					}
+					"-o" | "--output" => {
+						let output = match args.next() {
+							Some(path) => PathBuf::from(path.as_ref()),
+							None => {
+								return Action::Invalid(format!("{} requires a path", arg));
+							}
+						};
+						options = options.output(output);
+					}
+					"--stats" => {
+						options = options.stats(true);
+					}
+					"--check" => {
+						options = options.check(true);
 					}
-					"-v" | "-V" | "--version" => {
-						println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
-						exit(0);
+					"--discard-comments" => {
+						options = options.discard_comments(true);
 					}
-					_ => {
-						println!("unrecognized option: {}", arg);
-						exit(1);
+					"--repl" => {
+						options = options.repl(true);
 					}
+					"-h" | "--help" => return Action::Help,
+					"-v" | "-V" | "--version" => return Action::Version,
+					_ => return Action::Invalid(format!("unknown option: {}", arg)),
 				}
 			} else {
-				options.input = Some(PathBuf::from(arg));
+				options = options.input(arg);
 			}
 		}
 
-		options.into()
+		match options.build() {
+			Ok(options) => Action::Run(options),
+			Err(message) => Action::Invalid(message),
+		}
 	}
 }
 
+/// Takes the number given to `flag` from `args`, calling it `what` when reporting that it
+/// isn't a number, or isn't `valid`.
+fn number_arg<T: FromStr, S: AsRef<str>>(
+	args: &mut impl Iterator<Item = S>,
+	flag: &str,
+	what: &str,
+	valid: impl Fn(&T) -> bool,
+) -> Result<T, String> {
+	let Some(arg) = args.next() else {
+		return Err(format!("{} requires a number", flag));
+	};
+	let arg = arg.as_ref();
+	arg.parse()
+		.ok()
+		.filter(valid)
+		.ok_or_else(|| format!("invalid {}: {}", what, arg))
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -65,19 +499,369 @@ mod tests {
 	#[test]
 	fn from_args() {
 		assert_eq!(
-			Options::from_iter(["./howdy.yall"]),
-			Options {
-				debug_parser: false,
-				input: PathBuf::from("./howdy.yall"),
-			}
+			Action::from_iter(["./howdy.yall"]),
+			Action::Run(Options::builder().input("./howdy.yall").build().unwrap())
+		);
+
+		assert_eq!(
+			Action::from_iter(["./main.yall", "-p"]),
+			Action::Run(
+				Options::builder()
+					.debug_parser(true)
+					.input("./main.yall")
+					.build()
+					.unwrap()
+			)
+		);
+
+		assert_eq!(
+			Action::from_iter(["--emit", "json", "./main.yall"]),
+			Action::Run(
+				Options::builder()
+					.emit(Emit::Json)
+					.input("./main.yall")
+					.build()
+					.unwrap()
+			)
+		);
+	}
+
+	#[test]
+	fn from_args_emit() {
+		for (format, emit) in [
+			("debug", Emit::Debug),
+			("pretty", Emit::Pretty),
+			("sexpr", Emit::Sexpr),
+			("json", Emit::Json),
+			("diagnostics", Emit::Diagnostics),
+		] {
+			assert_eq!(
+				Action::from_iter(["--emit", format, "./main.yall"]),
+				Action::Run(
+					Options::builder()
+						.emit(emit)
+						.input("./main.yall")
+						.build()
+						.unwrap()
+				)
+			);
+		}
+	}
+
+	#[test]
+	fn from_args_max_depth() {
+		assert_eq!(
+			Action::from_iter(["--max-depth", "16", "./main.yall"]),
+			Action::Run(
+				Options::builder()
+					.max_depth(16)
+					.input("./main.yall")
+					.build()
+					.unwrap()
+			)
+		);
+	}
+
+	#[test]
+	fn from_args_max_nodes() {
+		assert_eq!(
+			Action::from_iter(["--max-nodes", "1000"]),
+			Action::Run(Options::builder().max_nodes(1000).build().unwrap())
 		);
+		assert_eq!(
+			Action::from_iter(["--max-nodes", "many"]),
+			Action::Invalid("invalid max nodes: many".to_string())
+		);
+	}
 
+	#[test]
+	fn from_args_max_lens() {
 		assert_eq!(
-			Options::from_iter(["./main.yall", "-p"]),
-			Options {
+			Action::from_iter(["--max-string-len", "64", "--max-identifier-len", "16"]),
+			Action::Run(
+				Options::builder()
+					.max_string_len(64)
+					.max_identifier_len(16)
+					.build()
+					.unwrap()
+			)
+		);
+		assert_eq!(
+			Action::from_iter(["--max-string-len", "long"]),
+			Action::Invalid("invalid max string length: long".to_string())
+		);
+		assert_eq!(
+			Action::from_iter(["--max-identifier-len"]),
+			Action::Invalid("--max-identifier-len requires a number".to_string())
+		);
+	}
+
+	#[test]
+	fn from_args_max_errors() {
+		assert_eq!(
+			Action::from_iter(["--max-errors", "5"]),
+			Action::Run(Options::builder().max_errors(5).build().unwrap())
+		);
+		for invalid in ["0", "few"] {
+			assert_eq!(
+				Action::from_iter(["--max-errors", invalid]),
+				Action::Invalid(format!("invalid max errors: {}", invalid))
+			);
+		}
+		assert_eq!(
+			Action::from_iter(["--max-errors"]),
+			Action::Invalid("--max-errors requires a number".to_string())
+		);
+	}
+
+	#[test]
+	fn from_args_brackets() {
+		assert_eq!(
+			Action::from_iter(["--brackets", "(["]),
+			Action::Run(
+				Options::builder()
+					.brackets(vec![ExpressionKind::Item, ExpressionKind::List])
+					.build()
+					.unwrap()
+			)
+		);
+		for invalid in ["", "(<"] {
+			assert_eq!(
+				Action::from_iter(["--brackets", invalid]),
+				Action::Invalid(format!("invalid brackets: {}", invalid))
+			);
+		}
+	}
+
+	#[test]
+	fn from_args_tab_width() {
+		assert_eq!(
+			Action::from_iter(["--tab-width", "4"]),
+			Action::Run(Options::builder().tab_width(4).build().unwrap())
+		);
+		assert_eq!(
+			Action::from_iter(["--tab-width", "0"]),
+			Action::Invalid("invalid tab width: 0".to_string())
+		);
+	}
+
+	#[test]
+	fn from_args_stats() {
+		assert_eq!(
+			Action::from_iter(["./main.yall", "--stats"]),
+			Action::Run(
+				Options::builder()
+					.stats(true)
+					.input("./main.yall")
+					.build()
+					.unwrap()
+			)
+		);
+	}
+
+	#[test]
+	fn from_args_discard_comments() {
+		assert_eq!(
+			Action::from_iter(["--discard-comments"]),
+			Action::Run(Options::builder().discard_comments(true).build().unwrap())
+		);
+	}
+
+	#[test]
+	fn from_args_check() {
+		assert_eq!(
+			Action::from_iter(["--check", "./main.yall"]),
+			Action::Run(
+				Options::builder()
+					.check(true)
+					.input("./main.yall")
+					.build()
+					.unwrap()
+			)
+		);
+	}
+
+	#[test]
+	fn from_args_comment() {
+		let Action::Run(options) = Action::from_iter(["--comment", "//", "./main.yall"]) else {
+			panic!("expected options");
+		};
+		assert_eq!(options.comment_prefix, "//");
+
+		assert_eq!(
+			Action::from_iter(["--comment", "( "]),
+			Action::Invalid("invalid comment prefix: ( ".to_string())
+		);
+		assert_eq!(
+			Action::from_iter(["--comment"]),
+			Action::Invalid("--comment requires a prefix".to_string())
+		);
+	}
+
+	#[test]
+	fn from_args_repl() {
+		assert_eq!(
+			Action::from_iter(["--repl"]),
+			Action::Run(Options::builder().repl(true).build().unwrap())
+		);
+	}
+
+	#[test]
+	fn from_args_output() {
+		assert_eq!(
+			Action::from_iter(["--emit", "json", "./main.yall", "-o", "./out.json"]),
+			Action::Run(
+				Options::builder()
+					.emit(Emit::Json)
+					.output("./out.json")
+					.input("./main.yall")
+					.build()
+					.unwrap()
+			)
+		);
+		assert_eq!(
+			Action::from_iter(["./main.yall", "--output"]),
+			Action::Invalid("--output requires a path".to_string())
+		);
+		assert_eq!(
+			Action::from_iter(["--output", ""]),
+			Action::Invalid("output path can't be empty".to_string())
+		);
+	}
+
+	#[test]
+	fn builder() {
+		assert_eq!(
+			Options::builder()
+				.debug_parser(true)
+				.input("./a.yall")
+				.input("-")
+				.build(),
+			Ok(Options {
 				debug_parser: true,
-				input: PathBuf::from("./main.yall"),
-			}
+				emit: None,
+				max_depth: DEFAULT_MAX_DEPTH,
+				max_nodes: None,
+				max_string_len: None,
+				max_identifier_len: None,
+				max_errors: DEFAULT_MAX_ERRORS,
+				brackets: ALL_BRACKETS.to_vec(),
+				comment_prefix: DEFAULT_COMMENT_PREFIX.to_string(),
+				discard_comments: false,
+				tab_width: 1,
+				repl: false,
+				stats: false,
+				check: false,
+				output: None,
+				inputs: vec![Input::File(PathBuf::from("./a.yall")), Input::Stdin],
+			})
+		);
+		assert_eq!(
+			Options::builder().input("").build(),
+			Err("input paths can't be empty".to_string())
 		);
 	}
+
+	#[test]
+	fn builder_matches_args() {
+		let built = Options::builder()
+			.emit(Emit::Sexpr)
+			.max_depth(16)
+			.input("./main.yall")
+			.build()
+			.unwrap();
+		assert_eq!(
+			Action::from_iter(["--emit", "sexpr", "--max-depth", "16", "./main.yall"]),
+			Action::Run(built)
+		);
+		assert_eq!(
+			Action::from_iter([""]),
+			Action::Invalid("input paths can't be empty".to_string())
+		);
+	}
+
+	#[test]
+	fn from_args_multiple() {
+		assert_eq!(
+			Action::from_iter(["-p", "./a.yall", "-", "./b.yall"]),
+			Action::Run(
+				Options::builder()
+					.debug_parser(true)
+					.input("./a.yall")
+					.input("-")
+					.input("./b.yall")
+					.build()
+					.unwrap()
+			)
+		);
+	}
+
+	#[test]
+	fn from_args_stdin() {
+		assert_eq!(
+			Action::from_iter(["-p"]),
+			Action::Run(Options::builder().debug_parser(true).build().unwrap())
+		);
+
+		assert_eq!(
+			Action::from_iter(["-p", "-"]),
+			Action::Run(Options::builder().debug_parser(true).build().unwrap())
+		);
+	}
+
+	#[test]
+	fn from_args_help() {
+		assert_eq!(Action::from_iter(["-h"]), Action::Help);
+		assert_eq!(Action::from_iter(["./main.yall", "--help"]), Action::Help);
+	}
+
+	#[test]
+	fn from_args_version() {
+		assert_eq!(Action::from_iter(["-V"]), Action::Version);
+		assert_eq!(Action::from_iter(["--version"]), Action::Version);
+	}
+
+	#[test]
+	fn from_args_invalid() {
+		assert_eq!(
+			Action::from_iter(["--frobnicate"]),
+			Action::Invalid("unknown option: --frobnicate".to_string())
+		);
+		assert_eq!(
+			Action::from_iter(["--emit", "xml"]),
+			Action::Invalid("unknown emit format: xml".to_string())
+		);
+		assert_eq!(
+			Action::from_iter(["--emit"]),
+			Action::Invalid("--emit requires a format".to_string())
+		);
+		assert_eq!(
+			Action::from_iter(["--max-depth", "deep"]),
+			Action::Invalid("invalid max depth: deep".to_string())
+		);
+	}
+
+	#[test]
+	fn parser_limits() {
+		use yall::parser::parse_program;
+		use yall::parser::ParseError;
+
+		let options = Options::builder()
+			.max_nodes(2)
+			.max_string_len(3)
+			.max_identifier_len(3)
+			.build()
+			.unwrap();
+		let parse = |source| parse_program(&mut options.parser(source));
+		assert!(parse("(abc)").is_ok());
+		assert!(matches!(parse("(a b c)"), Err(ParseError::TooLarge { .. })));
+		assert!(matches!(
+			parse("(\"abcd\")"),
+			Err(ParseError::TokenTooLong { .. })
+		));
+		assert!(matches!(
+			parse("(abcd)"),
+			Err(ParseError::TokenTooLong { .. })
+		));
+	}
 }
@@ -0,0 +1,571 @@
+//! A parser which borrows from the source wherever it can, instead of copying every
+//! identifier, comment, and string into a new allocation. It accepts exactly the same
+//! language as the owned parser, and its output can always be turned into the owned
+//! form with `into_owned`.
+
+use std::borrow::Cow;
+use std::ops::Deref;
+use std::str::Chars;
+
+use super::phrase::*;
+use super::Expression;
+use super::ExpressionKind;
+use super::Number;
+use super::ParseError;
+use super::Parser;
+use super::Program;
+use super::Span;
+use super::Spanned;
+
+/// A whole program, borrowing from the source just like [`BorrowedExpression`] does.
+/// See [`Program`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BorrowedProgram<'a> {
+	pub expressions: Vec<BorrowedExpression<'a>>,
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+	pub trailing_comments: Vec<&'a str>,
+}
+
+impl BorrowedProgram<'_> {
+	pub fn into_owned(self) -> Program {
+		Program::new(
+			self.expressions
+				.into_iter()
+				.map(BorrowedExpression::into_owned)
+				.collect(),
+			self.trailing_comments
+				.into_iter()
+				.map(str::to_string)
+				.collect(),
+		)
+	}
+}
+
+impl<'a> Deref for BorrowedProgram<'a> {
+	type Target = Vec<BorrowedExpression<'a>>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.expressions
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BorrowedExpression<'a> {
+	pub kind: ExpressionKind,
+	pub values: Vec<Spanned<BorrowedPhrase<'a>>>,
+	pub leading_comments: Vec<&'a str>,
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+	pub tail: Option<Box<Spanned<BorrowedPhrase<'a>>>>,
+	pub span: Span,
+}
+
+impl BorrowedExpression<'_> {
+	pub fn into_owned(self) -> Expression {
+		Expression {
+			kind: self.kind,
+			values: self
+				.values
+				.into_iter()
+				.map(|value| Spanned::new(value.node.into_owned(), value.span))
+				.collect(),
+			leading_comments: self
+				.leading_comments
+				.into_iter()
+				.map(str::to_string)
+				.collect(),
+			tail: self
+				.tail
+				.map(|tail| Box::new(Spanned::new(tail.node.into_owned(), tail.span))),
+			span: self.span,
+		}
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum BorrowedPhrase<'a> {
+	Expression(BorrowedExpression<'a>),
+	/// Only quoted identifiers like `|a b|` need to be copied.
+	Identifier(Cow<'a, str>),
+	/// Only strings containing escape sequences need to be copied, so that the escapes
+	/// can be decoded.
+	Text(Cow<'a, str>),
+	Char(char),
+	Number(Number),
+	Comment(&'a str),
+	Keyword(&'a str),
+	Boolean(bool),
+	Nil,
+}
+
+impl BorrowedPhrase<'_> {
+	pub fn into_owned(self) -> Phrase {
+		match self {
+			Self::Expression(expression) => Phrase::Expression(expression.into_owned()),
+			Self::Identifier(identifier) => Phrase::Identifier(identifier.into_owned()),
+			Self::Text(text) => Phrase::Text(text.into_owned()),
+			Self::Char(c) => Phrase::Char(c),
+			Self::Number(number) => Phrase::Number(number),
+			Self::Comment(body) => Phrase::Comment(body.to_string()),
+			Self::Keyword(keyword) => Phrase::Keyword(keyword.to_string()),
+			Self::Boolean(boolean) => Phrase::Boolean(boolean),
+			Self::Nil => Phrase::Nil,
+		}
+	}
+}
+
+/// Wraps a `Parser` along with the source it reads from, so that anything it consumes
+/// can be handed back out as a slice.
+pub struct BorrowedParser<'a> {
+	source: &'a str,
+	parser: Parser<Chars<'a>>,
+}
+
+impl<'a> From<&'a str> for BorrowedParser<'a> {
+	fn from(source: &'a str) -> Self {
+		// The parser skips over any byte order mark or shebang line, but its offsets
+		// still count them
+		Self {
+			source,
+			parser: source.into(),
+		}
+	}
+}
+
+impl<'a> BorrowedParser<'a> {
+	/// Everything consumed since `start`, which is a byte offset into the source.
+	fn since(&self, start: usize) -> &'a str {
+		&self.source[start..self.parser.offset()]
+	}
+}
+
+pub fn parse_borrowed_program<'a>(
+	s: &mut BorrowedParser<'a>,
+) -> Result<BorrowedProgram<'a>, ParseError> {
+	let mut program = BorrowedProgram::default();
+	loop {
+		let comments = parse_borrowed_top_level_comments(s)?;
+		if s.parser.peek().is_none() {
+			program.trailing_comments = comments;
+			return Ok(program);
+		}
+		let trailing = !program.is_empty();
+		let expression = parse_borrowed_top_level_expression(s, trailing, comments)?;
+		program.expressions.push(expression);
+	}
+}
+
+pub fn parse_borrowed_expression<'a>(
+	s: &mut BorrowedParser<'a>,
+) -> Result<BorrowedExpression<'a>, ParseError> {
+	let leading_comments = parse_borrowed_top_level_comments(s)?;
+	parse_borrowed_top_level_expression(s, false, leading_comments)
+}
+
+fn parse_borrowed_top_level_comments<'a>(
+	s: &mut BorrowedParser<'a>,
+) -> Result<Vec<&'a str>, ParseError> {
+	let mut comments = vec![];
+	loop {
+		skip_datum_comments(&mut s.parser)?;
+
+		let comment = match s.parser.peek() {
+			Some(';') => parse_borrowed_comment(s)?,
+			Some('#') => parse_borrowed_block_comment(s)?,
+			_ => break,
+		};
+		if let BorrowedPhrase::Comment(body) = comment {
+			comments.push(body);
+		}
+	}
+	Ok(comments)
+}
+
+fn parse_borrowed_top_level_expression<'a>(
+	s: &mut BorrowedParser<'a>,
+	trailing: bool,
+	leading_comments: Vec<&'a str>,
+) -> Result<BorrowedExpression<'a>, ParseError> {
+	let start = s.parser.location();
+	let initiator = s.parser.next();
+	let kind = initiator
+		.and_then(ExpressionKind::from_initiator)
+		.ok_or(ParseError::not_an_expression(initiator, trailing, start))?;
+	s.parser.enter(start)?;
+
+	let mut values: Vec<Spanned<BorrowedPhrase>> = vec![];
+	let mut tail = None;
+	loop {
+		skip_datum_comments(&mut s.parser)?;
+
+		let location = s.parser.location();
+		match s.parser.peek().copied() {
+			None | Some(')' | ']' | '}') => break,
+			Some(_) if tail.is_some() => return Err(ParseError::MalformedPair { location }),
+			Some('.')
+				if is_dot(&mut s.parser)
+					&& values
+						.iter()
+						.all(|value| matches!(value.node, BorrowedPhrase::Comment(_))) =>
+			{
+				s.parser.next();
+				values.push(Spanned::new(
+					BorrowedPhrase::Identifier(Cow::Borrowed(".")),
+					Span::new(location, s.parser.location()),
+				));
+			}
+			Some('.') if is_dot(&mut s.parser) => {
+				if kind != ExpressionKind::Item {
+					return Err(ParseError::MalformedPair { location });
+				}
+				s.parser.next();
+				skip_datum_comments(&mut s.parser)?;
+				tail = match s.parser.peek().copied() {
+					None | Some(')' | ']' | '}' | ';') => {
+						return Err(ParseError::MalformedPair {
+							location: s.parser.location(),
+						})
+					}
+					Some('#') if peek_hash_boolean(&mut s.parser).is_none() => {
+						return Err(ParseError::MalformedPair {
+							location: s.parser.location(),
+						})
+					}
+					Some(_) => Some(Box::new(parse_borrowed_phrase(s)?)),
+				};
+			}
+			Some(_) => values.push(parse_borrowed_phrase(s)?),
+		}
+	}
+
+	let location = s.parser.location();
+	let terminator = s.parser.next();
+	if terminator != Some(kind.terminator()) {
+		return Err(ParseError::unterminated(&kind, terminator, start, location));
+	}
+	s.parser.exit();
+
+	Ok(BorrowedExpression {
+		kind,
+		values,
+		leading_comments,
+		tail,
+		span: Span::new(start, s.parser.location()),
+	})
+}
+
+fn parse_borrowed_string<'a>(s: &mut BorrowedParser<'a>) -> Result<BorrowedPhrase<'a>, ParseError> {
+	let location = s.parser.location();
+
+	// Consume "
+	s.parser.expect('"', "a string")?;
+
+	let start = s.parser.offset();
+	let mut text = Cow::Borrowed("");
+	loop {
+		let end = s.parser.offset();
+		match s.parser.next() {
+			Some('"') => {
+				if let Cow::Borrowed(_) = text {
+					text = Cow::Borrowed(&s.source[start..end]);
+				}
+				break;
+			}
+			Some('\\') => {
+				// Up until the first escape, everything could still be borrowed
+				if let Cow::Borrowed(_) = text {
+					text = Cow::Owned(s.source[start..end].to_string());
+				}
+				if !skip_line_continuation(&mut s.parser) {
+					text.to_mut().push(parse_escape(&mut s.parser)?);
+				}
+			}
+			Some(c) => {
+				if let Cow::Owned(text) = &mut text {
+					text.push(c);
+				}
+			}
+			None => {
+				return Err(ParseError::UnterminatedString {
+					location,
+					end: s.parser.location(),
+				})
+			}
+		}
+	}
+
+	Ok(BorrowedPhrase::Text(text))
+}
+
+fn parse_borrowed_comment<'a>(
+	s: &mut BorrowedParser<'a>,
+) -> Result<BorrowedPhrase<'a>, ParseError> {
+	// Consume ;
+	s.parser.expect(';', "a comment")?;
+
+	let start = s.parser.offset();
+	s.parser
+		.peek_while(|&c| c != '\n' && c != '\r')
+		.for_each(drop);
+	let body = s.since(start);
+	// Consume the line break too, just like the owned parser
+	s.parser.next();
+
+	Ok(BorrowedPhrase::Comment(body))
+}
+
+fn parse_borrowed_block_comment<'a>(
+	s: &mut BorrowedParser<'a>,
+) -> Result<BorrowedPhrase<'a>, ParseError> {
+	let location = s.parser.location();
+
+	// Consume #|
+	s.parser.expect('#', "a block comment")?;
+	s.parser.expect('|', "a block comment")?;
+
+	let start = s.parser.offset();
+	let mut depth = 1;
+	loop {
+		let end = s.parser.offset();
+		match s.parser.next() {
+			Some('|') if s.parser.peek() == Some(&'#') => {
+				s.parser.next();
+				depth -= 1;
+				if depth == 0 {
+					return Ok(BorrowedPhrase::Comment(&s.source[start..end]));
+				}
+			}
+			Some('#') if s.parser.peek() == Some(&'|') => {
+				s.parser.next();
+				depth += 1;
+			}
+			Some(_) => {}
+			None => return Err(ParseError::UnterminatedComment { location }),
+		}
+	}
+}
+
+fn parse_borrowed_number<'a>(s: &mut BorrowedParser<'a>) -> Result<BorrowedPhrase<'a>, ParseError> {
+	let location = s.parser.location();
+	let literal = scan_number(&mut s.parser, String::new())?;
+	Number::from_literal(&literal, location).map(BorrowedPhrase::Number)
+}
+
+fn parse_borrowed_text_identifier<'a>(
+	s: &mut BorrowedParser<'a>,
+) -> Result<BorrowedPhrase<'a>, ParseError> {
+	let start = s.parser.offset();
+	s.parser
+		.peek_while(|&c| is_identifier_character(c))
+		.for_each(drop);
+
+	Ok(text_identifier(s.since(start)))
+}
+
+fn text_identifier(identifier: &str) -> BorrowedPhrase<'_> {
+	match identifier {
+		"true" => BorrowedPhrase::Boolean(true),
+		"false" => BorrowedPhrase::Boolean(false),
+		"nil" => BorrowedPhrase::Nil,
+		identifier => BorrowedPhrase::Identifier(Cow::Borrowed(identifier)),
+	}
+}
+
+/// Raw strings are rare enough that they just get copied, rather than needing their own
+/// copy of the rules for where they end.
+fn parse_borrowed_raw_string<'a>(
+	s: &mut BorrowedParser<'a>,
+) -> Result<BorrowedPhrase<'a>, ParseError> {
+	let start = s.parser.offset();
+	Ok(match parse_raw_string(&mut s.parser)? {
+		Phrase::Text(text) => BorrowedPhrase::Text(Cow::Owned(text)),
+		_ => text_identifier(s.since(start)),
+	})
+}
+
+fn parse_borrowed_operator_identifier<'a>(
+	s: &mut BorrowedParser<'a>,
+) -> Result<BorrowedPhrase<'a>, ParseError> {
+	let location = s.parser.location();
+	let start = s.parser.offset();
+	s.parser
+		.peek_while(|c| OPERATOR_CHARACTERS.contains(c))
+		.for_each(drop);
+	let identifier = s.since(start);
+
+	// A lone sign directly followed by a digit is actually a signed number, like `-5`
+	let is_sign = identifier == "-" || identifier == "+";
+	if is_sign && s.parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+		let literal = scan_number(&mut s.parser, identifier.to_string())?;
+		return Number::from_literal(&literal, location).map(BorrowedPhrase::Number);
+	}
+
+	Ok(BorrowedPhrase::Identifier(Cow::Borrowed(identifier)))
+}
+
+/// Quoted identifiers are rare enough that they just get copied too.
+fn parse_borrowed_quoted_identifier<'a>(
+	s: &mut BorrowedParser<'a>,
+) -> Result<BorrowedPhrase<'a>, ParseError> {
+	let identifier = scan_quoted_identifier(&mut s.parser)?;
+	Ok(BorrowedPhrase::Identifier(Cow::Owned(identifier)))
+}
+
+fn parse_borrowed_keyword<'a>(
+	s: &mut BorrowedParser<'a>,
+) -> Result<BorrowedPhrase<'a>, ParseError> {
+	s.parser.expect(':', "a keyword")?;
+
+	let location = s.parser.location();
+	let start = s.parser.offset();
+	s.parser
+		.peek_while(|&c| is_identifier_character(c))
+		.for_each(drop);
+	let keyword = s.since(start);
+	if keyword.is_empty() {
+		return Err(ParseError::unexpected(
+			s.parser.peek().copied(),
+			"the name of a keyword",
+			location,
+		));
+	}
+
+	Ok(BorrowedPhrase::Keyword(keyword))
+}
+
+pub fn parse_borrowed_phrase<'a>(
+	s: &mut BorrowedParser<'a>,
+) -> Result<Spanned<BorrowedPhrase<'a>>, ParseError> {
+	skip_datum_comments(&mut s.parser)?;
+
+	let location = s.parser.location();
+	if let Some(quote) = parse_quote_prefix(&mut s.parser) {
+		let name = Spanned::new(
+			BorrowedPhrase::Identifier(Cow::Borrowed(quote.name())),
+			Span::new(location, s.parser.location()),
+		);
+		s.parser.enter(location)?;
+		let quoted = parse_borrowed_phrase(s)?;
+		s.parser.exit();
+
+		let span = Span::new(location, quoted.span.end);
+		let expression = BorrowedExpression {
+			kind: ExpressionKind::Item,
+			values: vec![name, quoted],
+			leading_comments: vec![],
+			tail: None,
+			span,
+		};
+		return Ok(Spanned::new(BorrowedPhrase::Expression(expression), span));
+	}
+
+	let next = *s.parser.peek().ok_or(ParseError::UnexpectedEof {
+		expected: EXPECTED_PHRASE,
+		location,
+	})?;
+	let node = match next {
+		'(' | '[' | '{' => parse_borrowed_expression(s).map(BorrowedPhrase::Expression),
+		'"' => parse_borrowed_string(s),
+		';' => parse_borrowed_comment(s),
+		'#' if peek_hash_boolean(&mut s.parser).is_some() => {
+			scan_hash_boolean(&mut s.parser).map(BorrowedPhrase::Boolean)
+		}
+		'#' => parse_borrowed_block_comment(s),
+		':' => parse_borrowed_keyword(s),
+		'\'' => scan_char(&mut s.parser).map(BorrowedPhrase::Char),
+		'.' if is_dot(&mut s.parser) => Err(ParseError::MalformedPair { location }),
+		'.' => parse_borrowed_number(s),
+		x if x.is_ascii_digit() => parse_borrowed_number(s),
+		'r' => parse_borrowed_raw_string(s),
+		x if x.is_alphabetic() => parse_borrowed_text_identifier(s),
+		'|' if is_quoted_identifier(&mut s.parser) => parse_borrowed_quoted_identifier(s),
+		x if OPERATOR_CHARACTERS.contains(&x) => parse_borrowed_operator_identifier(s),
+		x => Err(ParseError::unexpected(Some(x), EXPECTED_PHRASE, location)),
+	}?;
+	let span = Span::new(location, s.parser.location());
+
+	if s.parser.peek() == Some(&':') {
+		parse_type(&mut s.parser)?;
+	};
+
+	Ok(Spanned::new(node, span))
+}
+
+#[cfg(test)]
+mod parse_borrowed_program_tests {
+	use super::*;
+	use crate::parser::parse_program;
+
+	#[test]
+	fn matches_owned() {
+		let source = r##"; a comment
+			(func add (a::int b) ; another comment
+				(+ a b))
+			#| a comment
+			   across lines |#
+			{hey "there" "\"friend\"\n" 'x' '\'' 'y `(z ,w ,@v)}
+			[1 -2.5 0x10 1e100 .5 true false nil :label -> |> r"\d+" r#"a "b""# raw]
+			(a . b) (c d . 'e) (a #; b #; #; (c) 'd e) (. a b) [. c]
+			(#t #f #true #false . #t)
+			("across
+			lines" "joined \
+			together")
+			; at the very end
+		"##;
+
+		let borrowed = parse_borrowed_program(&mut source.into()).unwrap();
+		let owned = parse_program(&mut source.into()).unwrap();
+		assert_eq!(borrowed.into_owned(), owned);
+	}
+
+	#[test]
+	fn borrows() {
+		let source = r#"(hello "friend" "\tescaped" |quoted one|)"#;
+		let program = parse_borrowed_program(&mut source.into()).unwrap();
+		let values = &program[0].values;
+		assert!(matches!(
+			values[0].node,
+			BorrowedPhrase::Identifier(Cow::Borrowed("hello"))
+		));
+		assert!(matches!(
+			values[1].node,
+			BorrowedPhrase::Text(Cow::Borrowed("friend"))
+		));
+		assert!(matches!(
+			values[2].node,
+			BorrowedPhrase::Text(Cow::Owned(_))
+		));
+		assert!(matches!(
+			&values[3].node,
+			BorrowedPhrase::Identifier(Cow::Owned(identifier)) if identifier == "quoted one"
+		));
+	}
+
+	#[test]
+	fn same_errors() {
+		for source in [
+			"(a \"b)",
+			"(a 'bc')",
+			"(a |b)",
+			"(a",
+			"#| a",
+			"(a b]",
+			"(a . b . c)",
+			"(a . #| b |#)",
+			"(a #tx)",
+			"(a 123abc)",
+			"(a) b",
+			")",
+			"(a \0)",
+			"(a) \u{7f}",
+		] {
+			assert_eq!(
+				parse_borrowed_program(&mut source.into()).unwrap_err(),
+				parse_program(&mut source.into()).unwrap_err()
+			);
+		}
+	}
+}
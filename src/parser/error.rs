@@ -0,0 +1,402 @@
+use miette::Diagnostic;
+use std::str::Utf8Error;
+use thiserror::Error;
+
+use super::display_width;
+use super::ExpressionKind;
+use super::Location;
+use super::Parser;
+use super::Span;
+use super::BYTE_ORDER_MARK;
+
+#[derive(Clone, Debug, Diagnostic, Eq, Error, PartialEq)]
+pub enum ParseError {
+	#[error(
+		"expected {expected} to terminate expression opened at {}, found {}",
+		display_location(.opened_at),
+		display_found(.found)
+	)]
+	UnbalancedDelimiter {
+		expected: char,
+		found: Option<char>,
+		/// Where the expression that was never closed begins.
+		opened_at: Location,
+		location: Location,
+	},
+
+	#[error(
+		"mismatched delimiter, expression opened with {opened} at {} but closed with {closed}",
+		display_location(.opened_at)
+	)]
+	MismatchedDelimiter {
+		opened: char,
+		closed: char,
+		opened_at: Location,
+		location: Location,
+	},
+
+	#[error("unexpected character {found}, expected {expected}")]
+	UnexpectedCharacter {
+		found: char,
+		expected: &'static str,
+		location: Location,
+	},
+
+	/// Control characters other than whitespace can only appear inside of literals, like
+	/// strings and comments, where they're taken as they are.
+	#[error(
+		"illegal character U+{:04X}, control characters are only allowed inside of strings and comments",
+		*.found as u32
+	)]
+	IllegalCharacter { found: char, location: Location },
+
+	#[error("unmatched {found}, there's no expression open for it to close")]
+	UnmatchedDelimiter { found: char, location: Location },
+
+	#[error("unexpected {found} after the end of an expression, expected another expression")]
+	UnexpectedTrailing { found: char, location: Location },
+
+	#[error("{found} isn't allowed to open an expression here")]
+	DisabledBrackets { found: char, location: Location },
+
+	#[error("malformed number")]
+	MalformedNumber { location: Location },
+
+	#[error("number is out of range")]
+	NumberOutOfRange { location: Location },
+
+	/// A number ran straight into something like an identifier, as in `123abc`, which is
+	/// much more likely to be a typo than two phrases meant to be written together.
+	#[error("unexpected {found} right after a number, expected whitespace or a delimiter")]
+	InvalidNumberSuffix { found: char, location: Location },
+
+	#[error("unterminated string")]
+	UnterminatedString {
+		location: Location,
+		/// Where the source ran out, so that the whole string can be pointed at.
+		end: Location,
+	},
+
+	#[error("unterminated quoted identifier")]
+	UnterminatedIdentifier { location: Location },
+
+	#[error("empty character literal")]
+	EmptyCharacter { location: Location },
+
+	#[error("character literal may only contain one character")]
+	MultipleCharacters { location: Location },
+
+	#[error("unterminated character literal")]
+	UnterminatedCharacter { location: Location },
+
+	#[error("unterminated block comment")]
+	UnterminatedComment { location: Location },
+
+	#[error("unknown escape sequence \\{found}")]
+	UnknownEscape { found: char, location: Location },
+
+	#[error("invalid unicode escape sequence")]
+	InvalidUnicodeEscape { location: Location },
+
+	#[error("invalid hex escape sequence, expected two hex digits no greater than 7F")]
+	InvalidHexEscape { location: Location },
+
+	#[error("expressions are nested more than {max_depth} deep")]
+	NestingTooDeep {
+		max_depth: usize,
+		location: Location,
+	},
+
+	#[error("too many errors, stopped after the first {max_errors}")]
+	TooManyErrors {
+		max_errors: usize,
+		location: Location,
+	},
+
+	#[error("{kind} is longer than {max_len} bytes")]
+	TokenTooLong {
+		/// What was too long, like a string or an identifier.
+		kind: &'static str,
+		max_len: usize,
+		location: Location,
+	},
+
+	#[error("program has more than {max_nodes} nodes")]
+	TooLarge {
+		max_nodes: usize,
+		location: Location,
+	},
+
+	#[error("malformed dotted pair, `.` must follow at least one value of an item, and come before exactly one more")]
+	MalformedPair { location: Location },
+
+	/// The source was given as bytes, and they stopped being UTF-8 at `location`.
+	#[error("source isn't valid UTF-8, starting at byte {}", .location.offset)]
+	InvalidUtf8 { location: Location },
+
+	#[error("failed to read source: {message}")]
+	Read { message: String, location: Location },
+
+	#[error("unexpected end of file, expected {expected}")]
+	UnexpectedEof {
+		expected: &'static str,
+		location: Location,
+	},
+}
+
+impl ParseError {
+	/// Builds the appropriate error for finding `found` where `expected` should have been.
+	pub fn unexpected(found: Option<char>, expected: &'static str, location: Location) -> Self {
+		match found {
+			Some(found) if is_illegal(found) => Self::IllegalCharacter { found, location },
+			Some(found) => Self::UnexpectedCharacter {
+				found,
+				expected,
+				location,
+			},
+			None => Self::UnexpectedEof { expected, location },
+		}
+	}
+
+	/// Builds the appropriate error for an expression of `kind`, opened at `opened_at`,
+	/// ending with `found` rather than its own terminator.
+	pub fn unterminated(
+		kind: &ExpressionKind,
+		found: Option<char>,
+		opened_at: Location,
+		location: Location,
+	) -> Self {
+		match found {
+			Some(closed @ (')' | ']' | '}')) => Self::MismatchedDelimiter {
+				opened: kind.initiator(),
+				closed,
+				opened_at,
+				location,
+			},
+			found => Self::UnbalancedDelimiter {
+				expected: kind.terminator(),
+				found,
+				opened_at,
+				location,
+			},
+		}
+	}
+
+	/// Builds the appropriate error for finding `found` at the top level of a program,
+	/// where only an expression can begin. `trailing` is whether another expression has
+	/// already ended before it.
+	pub fn not_an_expression(found: Option<char>, trailing: bool, location: Location) -> Self {
+		match found {
+			Some(found @ (')' | ']' | '}')) => Self::UnmatchedDelimiter { found, location },
+			Some(found) if trailing && !is_illegal(found) => {
+				Self::UnexpectedTrailing { found, location }
+			}
+			found => Self::unexpected(found, "an expression", location),
+		}
+	}
+
+	/// Builds the error for `bytes` failing to be decoded as UTF-8, pointing at the first
+	/// byte that isn't, with its line and column counted just like a parser would.
+	pub fn invalid_utf8(bytes: &[u8], error: Utf8Error) -> Self {
+		let valid = &bytes[..error.valid_up_to()];
+		// Everything before the error was already found to be valid
+		let mut parser = Parser::from(std::str::from_utf8(valid).unwrap_or_default());
+		while parser.next().is_some() {}
+		Self::InvalidUtf8 {
+			location: parser.location(),
+		}
+	}
+
+	/// Whether the only thing wrong is that the source ended too early, like in the
+	/// middle of an expression or a string, so that more source could still fix it.
+	pub fn is_eof(&self) -> bool {
+		matches!(
+			self,
+			Self::UnbalancedDelimiter { found: None, .. }
+				| Self::UnexpectedEof { .. }
+				| Self::UnterminatedString { .. }
+				| Self::UnterminatedIdentifier { .. }
+				| Self::UnterminatedComment { .. }
+		)
+	}
+
+	/// Where in the source the error was found.
+	pub fn location(&self) -> Location {
+		match self {
+			Self::UnbalancedDelimiter { location, .. }
+			| Self::MismatchedDelimiter { location, .. }
+			| Self::UnexpectedCharacter { location, .. }
+			| Self::IllegalCharacter { location, .. }
+			| Self::DisabledBrackets { location, .. }
+			| Self::MalformedNumber { location }
+			| Self::NumberOutOfRange { location }
+			| Self::InvalidNumberSuffix { location, .. }
+			| Self::UnterminatedString { location, .. }
+			| Self::UnterminatedIdentifier { location }
+			| Self::EmptyCharacter { location }
+			| Self::MultipleCharacters { location }
+			| Self::UnterminatedCharacter { location }
+			| Self::UnterminatedComment { location }
+			| Self::UnknownEscape { location, .. }
+			| Self::InvalidUnicodeEscape { location }
+			| Self::InvalidHexEscape { location }
+			| Self::NestingTooDeep { location, .. }
+			| Self::TooLarge { location, .. }
+			| Self::TokenTooLong { location, .. }
+			| Self::TooManyErrors { location, .. }
+			| Self::MalformedPair { location }
+			| Self::UnmatchedDelimiter { location, .. }
+			| Self::UnexpectedTrailing { location, .. }
+			| Self::InvalidUtf8 { location }
+			| Self::Read { location, .. }
+			| Self::UnexpectedEof { location, .. } => *location,
+		}
+	}
+
+	/// The region of the source the error is about. Most errors are about a single
+	/// character, and only cover where it was, but some cover a whole range.
+	pub fn span(&self) -> Span {
+		match self {
+			Self::UnterminatedString { location, end } => Span::new(*location, *end),
+			_ => Span::new(self.location(), self.location()),
+		}
+	}
+
+	/// Renders the error along with the line of `source` it was found on, and carets
+	/// underlining its span on that line.
+	pub fn render(&self, source: &str) -> String {
+		let Span {
+			start: location,
+			end,
+		} = self.span();
+		// Offsets count the byte order mark, but lines never include it
+		let before = source.get(..location.offset).unwrap_or(source);
+		let before = before.strip_prefix(BYTE_ORDER_MARK).unwrap_or(before);
+		let source = source.strip_prefix(BYTE_ORDER_MARK).unwrap_or(source);
+		let line = lines(source).nth(location.line as usize).unwrap_or("");
+		// Line numbers are shown counting from one, like any editor would
+		let number = (location.line + 1).to_string();
+		let gutter = " ".repeat(number.len());
+
+		// Tabs are kept as they are, so that the caret lines up however wide they
+		// end up being shown. The indent is taken from the offset rather than the
+		// column, because columns might count tabs as more than one.
+		let indent: String = before
+			.rsplit(['\n', '\r'])
+			.next()
+			.unwrap_or("")
+			.chars()
+			.map(|c| match c {
+				'\t' => "\t".to_string(),
+				c => " ".repeat(display_width(c)),
+			})
+			.collect();
+		// A span running past the end of the line is only underlined up to there
+		let underlined = source
+			.get(location.offset..end.offset.max(location.offset))
+			.unwrap_or("")
+			.split(['\n', '\r'])
+			.next()
+			.unwrap_or("")
+			.chars()
+			.map(display_width)
+			.sum::<usize>();
+		let carets = "^".repeat(underlined.max(1));
+
+		format!(
+			"error: {}\n{}--> {}:{}\n{} |\n{} | {}\n{} | {}{}",
+			self,
+			gutter,
+			number,
+			location.column + 1,
+			gutter,
+			number,
+			line,
+			gutter,
+			indent,
+			carets,
+		)
+	}
+}
+
+/// Splits `source` into lines the same way the parser counts them, at each `\r\n`, `\n`,
+/// or `\r` on its own.
+fn lines(source: &str) -> impl Iterator<Item = &str> {
+	source
+		.split('\n')
+		.flat_map(|line| line.strip_suffix('\r').unwrap_or(line).split('\r'))
+}
+
+/// Whether `c` can't appear anywhere outside of a literal.
+fn is_illegal(c: char) -> bool {
+	c.is_control() && !c.is_whitespace()
+}
+
+/// Shows a location counting lines and columns from one, like any editor would.
+fn display_location(location: &Location) -> String {
+	format!("{}:{}", location.line + 1, location.column + 1)
+}
+
+fn display_found(found: &Option<char>) -> String {
+	match found {
+		Some(c) => c.to_string(),
+		None => "end of file".to_string(),
+	}
+}
+
+#[cfg(test)]
+mod render_tests {
+	use crate::parse;
+
+	#[test]
+	fn missing_bracket() {
+		let source = "(add 1\n\t[2 3)\n(ok)";
+		let error = parse(source).unwrap_err();
+		assert_eq!(
+			error.render(source),
+			"error: mismatched delimiter, expression opened with [ at 2:2 but closed with )\n --> 2:6\n  |\n2 | \t[2 3)\n  | \t    ^"
+		);
+	}
+
+	#[test]
+	fn unterminated_string() {
+		let source = "(print\n  \"hello";
+		let error = parse(source).unwrap_err();
+		assert_eq!(
+			error.render(source),
+			"error: unterminated string\n --> 2:3\n  |\n2 |   \"hello\n  |   ^^^^^^"
+		);
+	}
+
+	#[test]
+	fn line_breaks() {
+		for source in ["(a)\r(b ]", "(a)\r\n(b ]", "(a)\n(b ]"] {
+			let error = parse(source).unwrap_err();
+			assert_eq!(
+				error.render(source),
+				"error: mismatched delimiter, expression opened with ( at 2:1 but closed with ]\n --> 2:4\n  |\n2 | (b ]\n  |    ^",
+				"{:?}",
+				source
+			);
+		}
+	}
+}
+
+#[cfg(test)]
+mod is_eof_tests {
+	use crate::parse;
+
+	#[test]
+	fn eof() {
+		for source in ["(a", "(a '", "(a \"b", "(|a", "#| a", "(a #;"] {
+			assert!(parse(source).unwrap_err().is_eof(), "{}", source);
+		}
+	}
+
+	#[test]
+	fn stray_characters() {
+		for source in ["(a]", "(a) b", ")", "(a . b c)", "(a \\)"] {
+			assert!(!parse(source).unwrap_err().is_eof(), "{}", source);
+		}
+	}
+}
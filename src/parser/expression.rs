@@ -1,42 +1,156 @@
-use miette::miette;
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
 
 use super::phrase::*;
+use super::ParseError;
 use super::Parser;
+use super::Span;
+use super::Spanned;
 
-#[allow(dead_code)]
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Expression {
-	kind: ExpressionKind,
-	values: Vec<Phrase>,
+	pub kind: ExpressionKind,
+	pub values: Vec<Spanned<Phrase>>,
+	/// The bodies of any comments directly preceding the expression, in order.
+	pub leading_comments: Vec<String>,
+	/// Whatever follows the `.` of a dotted pair, like the `b` in `(a . b)`. Only items
+	/// can have one.
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+	pub tail: Option<Box<Spanned<Phrase>>>,
+	pub span: Span,
+}
+
+// Dropping each nested expression from inside the one containing it would recurse once
+// for every level of nesting, so instead they're all taken out and dropped from here.
+impl Drop for Expression {
+	fn drop(&mut self) {
+		let mut nested = std::mem::take(&mut self.values);
+		nested.extend(self.tail.take().map(|tail| *tail));
+		while let Some(value) = nested.pop() {
+			if let Phrase::Expression(mut expression) = value.node {
+				nested.append(&mut expression.values);
+				nested.extend(expression.tail.take().map(|tail| *tail));
+			}
+		}
+	}
 }
 
 impl Expression {
-	pub fn null(value: Phrase) -> Self {
-		Self {
-			kind: ExpressionKind::Null,
-			values: vec![value],
+	/// Whether the expression holds nothing but comments, if even that. Comments inside
+	/// of brackets are just values of the expression they're in, so a form like
+	/// `( ; hi\n)` is still an `Item`, just an empty one.
+	pub fn is_empty(&self) -> bool {
+		self.tail.is_none()
+			&& self
+				.values
+				.iter()
+				.all(|value| matches!(value.node, Phrase::Comment(_)))
+	}
+
+	pub fn kind(&self) -> &ExpressionKind {
+		&self.kind
+	}
+
+	/// The text the expression was parsed from. See [`Span::source_text`].
+	pub fn source_text<'a>(&self, source: &'a str) -> &'a str {
+		self.span.source_text(source)
+	}
+
+	/// Iterates over the phrases directly inside the expression, ending with its tail if
+	/// it's a dotted pair.
+	pub fn iter(&self) -> Phrases<'_> {
+		Phrases {
+			values: self.values.iter(),
+			tail: self.tail.as_deref(),
+		}
+	}
+
+	/// How many nodes make up the expression, counting itself, and every phrase inside of
+	/// it, including any nested expressions and everything inside of those.
+	pub fn node_count(&self) -> usize {
+		let phrases = self.values.iter().chain(self.tail.as_deref());
+		1 + phrases
+			.map(|value| match &value.node {
+				Phrase::Expression(expression) => expression.node_count(),
+				_ => 1,
+			})
+			.sum::<usize>()
+	}
+
+	/// Rewrites every phrase in the expression with `f`, from the bottom up. Nested
+	/// expressions have their own phrases rewritten first, and are then handed to `f`
+	/// themselves, so `f` sees the finished version of each one.
+	pub fn map_phrases(mut self, f: &mut impl FnMut(Phrase) -> Phrase) -> Self {
+		self.values = std::mem::take(&mut self.values)
+			.into_iter()
+			.map(|value| map_phrase(value, f))
+			.collect();
+		self.tail = self.tail.take().map(|tail| Box::new(map_phrase(*tail, f)));
+		self
+	}
+}
+
+/// The phrases directly inside of an expression. See [`Expression::iter`].
+#[derive(Clone, Debug)]
+pub struct Phrases<'a> {
+	values: std::slice::Iter<'a, Spanned<Phrase>>,
+	tail: Option<&'a Spanned<Phrase>>,
+}
+
+impl<'a> Iterator for Phrases<'a> {
+	type Item = &'a Phrase;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		match self.values.next() {
+			Some(value) => Some(&value.node),
+			None => self.tail.take().map(|tail| &tail.node),
 		}
 	}
 }
 
+impl DoubleEndedIterator for Phrases<'_> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		match self.tail.take() {
+			Some(tail) => Some(&tail.node),
+			None => self.values.next_back().map(|value| &value.node),
+		}
+	}
+}
+
+impl<'a> IntoIterator for &'a Expression {
+	type Item = &'a Phrase;
+	type IntoIter = Phrases<'a>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ExpressionKind {
 	Block,
 	List,
 	Item,
-	Null,
 }
 
 impl ExpressionKind {
-	pub fn from_initiator(c: char) -> miette::Result<Self> {
+	pub fn from_initiator(c: char) -> Option<Self> {
 		match c {
-			'{' => Ok(Self::Block),
-			'[' => Ok(Self::List),
-			'(' => Ok(Self::Item),
-			c => Err(miette!(
-				"unexpected character {}, expected an expression",
-				c
-			)),
+			'{' => Some(Self::Block),
+			'[' => Some(Self::List),
+			'(' => Some(Self::Item),
+			_ => None,
+		}
+	}
+
+	pub fn initiator(&self) -> char {
+		match self {
+			Self::Block => '{',
+			Self::List => '[',
+			Self::Item => '(',
 		}
 	}
 
@@ -45,39 +159,602 @@ impl ExpressionKind {
 			Self::Block => '}',
 			Self::List => ']',
 			Self::Item => ')',
-			Self::Null => unreachable!(),
 		}
 	}
 }
 
+impl Display for Expression {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for comment in &self.leading_comments {
+			write_comment(f, comment)?;
+			writeln!(f)?;
+		}
+		write!(f, "{}", self.kind.initiator())?;
+		for (i, value) in self.values.iter().enumerate() {
+			if i > 0 {
+				// A line comment runs until the end of the line, so whatever follows
+				// one needs to start on the next.
+				let separator = match self.values[i - 1].is_line_comment() {
+					true => "\n",
+					false => " ",
+				};
+				write!(f, "{}", separator)?;
+			}
+			write!(f, "{}", value.node)?;
+		}
+		let ends_with_comment = self
+			.values
+			.last()
+			.is_some_and(|value| value.is_line_comment());
+		if let Some(tail) = &self.tail {
+			match ends_with_comment {
+				true => write!(f, "\n. {}", tail.node)?,
+				false => write!(f, " . {}", tail.node)?,
+			}
+		} else if ends_with_comment {
+			writeln!(f)?;
+		}
+		write!(f, "{}", self.kind.terminator())
+	}
+}
+
+/// Parses exactly one expression, which may have whitespace and comments before it, but
+/// only whitespace after it.
+impl FromStr for Expression {
+	type Err = ParseError;
+
+	fn from_str(source: &str) -> Result<Self, Self::Err> {
+		let mut parser = Parser::from(source);
+		let expression = parse_expression(&mut parser)?;
+
+		parse_whitespace(&mut parser)?;
+		let location = parser.location();
+		match parser.peek() {
+			Some(&found) => Err(ParseError::UnexpectedTrailing { found, location }),
+			None => Ok(expression),
+		}
+	}
+}
+
+fn map_phrase(value: Spanned<Phrase>, f: &mut impl FnMut(Phrase) -> Phrase) -> Spanned<Phrase> {
+	let node = match value.node {
+		Phrase::Expression(expression) => Phrase::Expression(expression.map_phrases(f)),
+		node => node,
+	};
+	Spanned::new(f(node), value.span)
+}
+
+#[cfg(test)]
+mod iter_tests {
+	use super::*;
+
+	#[test]
+	fn children() {
+		let expression: Expression = "(a b c)".parse().unwrap();
+		let mut names = vec![];
+		for phrase in &expression {
+			names.push(phrase.to_string());
+		}
+		assert_eq!(names, vec!["a", "b", "c"]);
+	}
+
+	#[test]
+	fn tail() {
+		let expression: Expression = "(a (b) . c)".parse().unwrap();
+		let phrases = expression.iter().map(Phrase::to_string).collect::<Vec<_>>();
+		assert_eq!(phrases, vec!["a", "(b)", "c"]);
+
+		let reversed = expression
+			.iter()
+			.rev()
+			.map(Phrase::to_string)
+			.collect::<Vec<_>>();
+		assert_eq!(reversed, vec!["c", "(b)", "a"]);
+	}
+}
+
+#[cfg(test)]
+mod is_empty_tests {
+	use super::*;
+
+	#[test]
+	fn only_a_comment() {
+		let expression: Expression = "( ; x\n )".parse().unwrap();
+		assert_eq!(expression.kind, ExpressionKind::Item);
+		assert!(expression.is_empty());
+		assert_eq!(expression.values[0].node, Phrase::Comment(" x".to_string()));
+	}
+
+	#[test]
+	fn not_empty() {
+		for source in ["(a)", "(; x\n a)", "(#| x |# b . a)"] {
+			let expression: Expression = source.parse().unwrap();
+			assert!(!expression.is_empty(), "{}", source);
+		}
+		assert!("[]".parse::<Expression>().unwrap().is_empty());
+	}
+
+	#[test]
+	fn display() {
+		for source in ["[]", "()", "{}"] {
+			let expression: Expression = source.parse().unwrap();
+			assert_eq!(expression.to_string(), source);
+		}
+	}
+}
+
+#[cfg(test)]
+mod node_count_tests {
+	use super::*;
+
+	#[test]
+	fn node_count() {
+		for (source, count) in [
+			("()", 1),
+			("(a b c)", 4),
+			("(a (b [c]) . d)", 7),
+			("(a 'b)", 5),
+			("(a ; comment\n b)", 4),
+		] {
+			let expression: Expression = source.parse().unwrap();
+			assert_eq!(expression.node_count(), count, "{}", source);
+		}
+	}
+}
+
+#[cfg(test)]
+mod map_phrases_tests {
+	use super::*;
+
+	#[test]
+	fn uppercase_identifiers() {
+		let expression: Expression = "(foo (bar baz))".parse().unwrap();
+		let uppercased = expression.map_phrases(&mut |phrase| match phrase {
+			Phrase::Identifier(name) => Phrase::Identifier(name.to_uppercase()),
+			phrase => phrase,
+		});
+		assert_eq!(uppercased, "(FOO (BAR BAZ))".parse().unwrap());
+	}
+
+	#[test]
+	fn bottom_up() {
+		let expression: Expression = "(a (b) . c)".parse().unwrap();
+		let mut seen = vec![];
+		expression.map_phrases(&mut |phrase| {
+			seen.push(phrase.to_string());
+			phrase
+		});
+		assert_eq!(seen, vec!["a", "b", "(b)", "c"]);
+	}
+}
+
+#[cfg(test)]
+mod from_str_tests {
+	use super::*;
+	use crate::parser::Location;
+
+	#[test]
+	fn single() {
+		let expression: Expression = "(a b)".parse().unwrap();
+		assert_eq!(expression.kind, ExpressionKind::Item);
+		assert_eq!(expression.values.len(), 2);
+	}
+
+	#[test]
+	fn surrounding_whitespace() {
+		let expression: Expression = "\n  ; doc\n  [a]  \n".parse().unwrap();
+		assert_eq!(expression.kind, ExpressionKind::List);
+		assert_eq!(expression.leading_comments, vec![" doc".to_string()]);
+	}
+
+	#[test]
+	fn trailing() {
+		assert_eq!(
+			"(a b) c".parse::<Expression>(),
+			Err(ParseError::UnexpectedTrailing {
+				found: 'c',
+				location: Location {
+					line: 0,
+					column: 6,
+					offset: 6
+				},
+			})
+		);
+		assert!("(a) (b)".parse::<Expression>().is_err());
+	}
+}
+
 pub fn parse_expression(
 	parser: &mut Parser<impl Iterator<Item = char>>,
-) -> miette::Result<Expression> {
-	parse_whitespace(parser)?;
+) -> Result<Expression, ParseError> {
+	let leading_comments = parse_top_level_comments(parser)?;
+	parse_top_level_expression(parser, false, leading_comments)
+}
+
+/// Parses the comments between top-level expressions, returning their bodies. These
+/// lead the expression after them, unless they're at the end of the program.
+pub(super) fn parse_top_level_comments(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<Vec<String>, ParseError> {
+	let mut comments = vec![];
+	loop {
+		skip_datum_comments(parser)?;
+
+		let comment = match parser.peek().copied() {
+			_ if parser.at_comment() => parse_comment(parser)?,
+			Some('#') => parse_block_comment(parser)?,
+			_ => break,
+		};
+		if let Phrase::Comment(body) = comment {
+			comments.push(body);
+		}
+	}
+	Ok(comments)
+}
 
-	if parser.peek() == Some(&';') {
-		return Ok(Expression::null(parse_comment(parser)?));
+/// Parses an expression at the top level of a program, after the comments leading it,
+/// where `trailing` is whether another expression has already ended before it, so that
+/// anything else found there can be reported as trailing after it.
+pub(super) fn parse_top_level_expression(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+	trailing: bool,
+	leading_comments: Vec<String>,
+) -> Result<Expression, ParseError> {
+	let initiator = parser.peek().copied();
+	if initiator.and_then(ExpressionKind::from_initiator).is_none() {
+		return Err(ParseError::not_an_expression(
+			initiator,
+			trailing,
+			parser.location(),
+		));
 	}
 
-	let kind = ExpressionKind::from_initiator(
-		parser
-			.next()
-			.ok_or(miette!("expected an expression here"))?,
-	)?;
+	let mut expression = parse_delimited_expression(parser)?;
+	expression.leading_comments = leading_comments;
+	Ok(expression)
+}
 
-	let mut values = vec![];
-	while let Ok(phrase) = parse_phrase(parser) {
-		values.push(phrase);
+/// Parses an expression from its opening bracket to its closing one. This is what nested
+/// expressions recurse through, so it's kept apart from the handling of comments before
+/// top-level expressions, to keep each level of recursion from using any more stack than
+/// it needs to.
+pub(super) fn parse_delimited_expression(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<Expression, ParseError> {
+	let start = parser.location();
+	let initiator = parser.next();
+	let kind = initiator
+		.and_then(ExpressionKind::from_initiator)
+		.ok_or(ParseError::unexpected(initiator, "an expression", start))?;
+	parser.check_brackets(&kind, start)?;
+	parser.enter(start)?;
+	parser.add_nodes(1, start)?;
+
+	let mut values: Vec<Spanned<Phrase>> = vec![];
+	let mut tail = None;
+	loop {
+		skip_datum_comments(parser)?;
+
+		// Any closing character, matching or not, ends the expression. Whether it
+		// was the right one gets checked below.
+		let location = parser.location();
+		match parser.peek().copied() {
+			None | Some(')' | ']' | '}') => break,
+			Some(_) if tail.is_some() => return Err(ParseError::MalformedPair { location }),
+			// A pair needs something before its `.`, and comments don't count, so a `.`
+			// with nothing before it is just an identifier, like in `(. object field)`
+			Some('.')
+				if is_dot(parser)
+					&& values
+						.iter()
+						.all(|value| matches!(value.node, Phrase::Comment(_))) =>
+			{
+				parser.add_nodes(1, location)?;
+				parser.next();
+				values.push(Spanned::new(
+					parser.identifier(".".to_string()),
+					Span::new(location, parser.location()),
+				));
+			}
+			Some('.') if is_dot(parser) => {
+				if kind != ExpressionKind::Item {
+					return Err(ParseError::MalformedPair { location });
+				}
+				parser.next();
+				skip_datum_comments(parser)?;
+				tail = Some(Box::new(parse_tail(parser)?));
+			}
+			Some(_) => values.push(parse_phrase(parser)?),
+		}
 	}
 
+	let location = parser.location();
 	let terminator = parser.next();
 	if terminator != Some(kind.terminator()) {
-		return Err(miette!(
-			"expected {} to terminate expression, got {:?}",
-			kind.terminator(),
-			terminator
+		return Err(ParseError::unterminated(&kind, terminator, start, location));
+	}
+	parser.exit();
+
+	Ok(Expression {
+		kind,
+		values,
+		leading_comments: vec![],
+		tail,
+		span: Span::new(start, parser.location()),
+	})
+}
+
+/// Parses the phrase after the `.` of a dotted pair, which can't be a comment, or be
+/// missing entirely.
+fn parse_tail(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<Spanned<Phrase>, ParseError> {
+	match parser.peek().copied() {
+		_ if parser.at_comment() => Err(ParseError::MalformedPair {
+			location: parser.location(),
+		}),
+		None | Some(')' | ']' | '}') => Err(ParseError::MalformedPair {
+			location: parser.location(),
+		}),
+		Some('#') if peek_hash_boolean(parser).is_none() => Err(ParseError::MalformedPair {
+			location: parser.location(),
+		}),
+		Some(_) => parse_phrase(parser),
+	}
+}
+
+#[cfg(test)]
+mod parse_expression_tests {
+	use super::*;
+	use crate::parser::Location;
+	use crate::parser::Number;
+
+	#[test]
+	fn span() {
+		let item = " (a\n b)";
+		let expression = parse_expression(&mut item.into()).unwrap();
+		assert_eq!(
+			expression.span,
+			Span::new(
+				Location {
+					line: 0,
+					column: 1,
+					offset: 1
+				},
+				Location {
+					line: 1,
+					column: 3,
+					offset: 7,
+				}
+			)
+		);
+	}
+
+	#[test]
+	fn leading_comments() {
+		let source = "; doc\n(foo)";
+		let program = crate::parser::parse_program(&mut source.into()).unwrap();
+		assert_eq!(program.len(), 1);
+		assert_eq!(program[0].kind, ExpressionKind::Item);
+		assert_eq!(program[0].leading_comments, vec![" doc".to_string()]);
+	}
+
+	#[test]
+	fn pairs() {
+		let pair = parse_expression(&mut "(a . b)".into()).unwrap();
+		assert_eq!(pair.values.len(), 1);
+		assert_eq!(
+			pair.tail.as_ref().unwrap().node,
+			Phrase::Identifier("b".to_string())
+		);
+
+		let improper = parse_expression(&mut "(a b . c)".into()).unwrap();
+		assert_eq!(improper.values.len(), 2);
+		assert_eq!(
+			improper.tail.as_ref().unwrap().node,
+			Phrase::Identifier("c".to_string())
+		);
+
+		let list = parse_expression(&mut "(a b c)".into()).unwrap();
+		assert_eq!(list.tail, None);
+	}
+
+	#[test]
+	fn dot_identifiers() {
+		let access = parse_expression(&mut "(. a b)".into()).unwrap();
+		assert_eq!(access.values[0].node, Phrase::Identifier(".".to_string()));
+		assert_eq!(access.values.len(), 3);
+		assert_eq!(access.tail, None);
+		// Written back out it's quoted, so that it stays an identifier wherever it ends up
+		assert_eq!(access.to_string(), "(|.| a b)");
+
+		let list = parse_expression(&mut "[; first\n. a]".into()).unwrap();
+		assert_eq!(list.values[1].node, Phrase::Identifier(".".to_string()));
+
+		// Once there's something before it, a `.` always makes a pair
+		let pair = parse_expression(&mut "(. . b)".into()).unwrap();
+		assert_eq!(pair.values.len(), 1);
+		assert_eq!(pair.values[0].node, Phrase::Identifier(".".to_string()));
+		assert_eq!(
+			pair.tail.as_ref().unwrap().node,
+			Phrase::Identifier("b".to_string())
+		);
+
+		let number = parse_expression(&mut "(.5)".into()).unwrap();
+		assert_eq!(number.values[0].node, Phrase::Number(Number::Float(0.5)));
+	}
+
+	#[test]
+	fn malformed_pairs() {
+		for (source, column) in [
+			("(a . b . c)", 7),
+			("(a . b c)", 7),
+			("(a .)", 4),
+			("[a . b]", 3),
+			("(. . b . c)", 7),
+		] {
+			assert_eq!(
+				parse_expression(&mut source.into()),
+				Err(ParseError::MalformedPair {
+					location: Location {
+						line: 0,
+						column,
+						offset: column as usize,
+					},
+				}),
+				"{}",
+				source
+			);
+		}
+	}
+
+	#[test]
+	fn unbalanced() {
+		let unclosed = "(a b";
+		assert!(matches!(
+			parse_expression(&mut unclosed.into()),
+			Err(ParseError::UnbalancedDelimiter {
+				expected: ')',
+				found: None,
+				..
+			})
 		));
 	}
 
-	Ok(Expression { kind, values })
+	#[test]
+	fn unbalanced_opened_at() {
+		let unclosed = "(a\n  [b\n   c\n  d";
+		let error = parse_expression(&mut unclosed.into()).unwrap_err();
+		assert_eq!(
+			error,
+			ParseError::UnbalancedDelimiter {
+				expected: ']',
+				found: None,
+				opened_at: Location {
+					line: 1,
+					column: 2,
+					offset: 5,
+				},
+				location: Location {
+					line: 3,
+					column: 3,
+					offset: 16,
+				},
+			}
+		);
+		assert_eq!(
+			error.to_string(),
+			"expected ] to terminate expression opened at 2:3, found end of file"
+		);
+		assert_eq!(crate::parse(unclosed).unwrap_err(), error);
+	}
+
+	#[test]
+	fn mismatched() {
+		let at = |column: u32| Location {
+			line: 0,
+			column,
+			offset: column as usize,
+		};
+		for (source, opened, closed, opened_at, location) in [
+			("(a b]", '(', ']', 0, 4),
+			("[a b}", '[', '}', 0, 4),
+			("{a b)", '{', ')', 0, 4),
+			("(a [b})", '[', '}', 3, 5),
+		] {
+			let error = parse_expression(&mut source.into()).unwrap_err();
+			assert_eq!(
+				error,
+				ParseError::MismatchedDelimiter {
+					opened,
+					closed,
+					opened_at: at(opened_at),
+					location: at(location),
+				},
+				"{}",
+				source
+			);
+			assert_eq!(crate::parse(source).unwrap_err(), error, "{}", source);
+		}
+	}
+
+	#[test]
+	fn nesting_too_deep() {
+		let deep = "(".repeat(10_000);
+		assert!(matches!(
+			parse_expression(&mut deep.as_str().into()),
+			Err(ParseError::NestingTooDeep {
+				max_depth: crate::parser::DEFAULT_MAX_DEPTH,
+				..
+			})
+		));
+
+		let nested = "((a))";
+		let mut parser = Parser::from(nested).with_max_depth(1);
+		assert!(matches!(
+			parse_expression(&mut parser),
+			Err(ParseError::NestingTooDeep { max_depth: 1, .. })
+		));
+		let mut parser = Parser::from(nested).with_max_depth(2);
+		assert!(parse_expression(&mut parser).is_ok());
+	}
+
+	#[test]
+	fn unterminated_string() {
+		let unterminated = "(a \"b)";
+		assert!(matches!(
+			parse_expression(&mut unterminated.into()),
+			Err(ParseError::UnterminatedString { .. })
+		));
+	}
+
+	#[test]
+	fn unexpected_character() {
+		let bad = "a";
+		assert!(matches!(
+			parse_expression(&mut bad.into()),
+			Err(ParseError::UnexpectedCharacter { found: 'a', .. })
+		));
+	}
+
+	#[test]
+	fn unexpected_trailing() {
+		let trailing = "(a)\n; comment\nb";
+		assert_eq!(
+			crate::parser::parse_program(&mut trailing.into()),
+			Err(ParseError::UnexpectedTrailing {
+				found: 'b',
+				location: Location {
+					line: 2,
+					column: 0,
+					offset: 14,
+				},
+			})
+		);
+	}
+
+	#[test]
+	fn unmatched_delimiter() {
+		for source in [")", "(a))", "(a) ]"] {
+			assert!(
+				matches!(
+					crate::parser::parse_program(&mut source.into()),
+					Err(ParseError::UnmatchedDelimiter { .. })
+				),
+				"{}",
+				source
+			);
+		}
+		assert_eq!(
+			parse_expression(&mut "}".into()),
+			Err(ParseError::UnmatchedDelimiter {
+				found: '}',
+				location: Location {
+					line: 0,
+					column: 0,
+					offset: 0
+				},
+			})
+		);
+	}
 }
@@ -0,0 +1,200 @@
+//! Hashing of what a program means, leaving out where anything was written and any
+//! comments, so that the same program hashes the same no matter how it was laid out.
+
+use super::Expression;
+use super::ExpressionKind;
+use super::Number;
+use super::Phrase;
+
+impl Expression {
+	/// A hash of the expression's structure and contents, which ignores spans and
+	/// comments, so that only what it holds matters and not how it was written. The hash is
+	/// computed the same way on every platform and version, so it can be stored, like in
+	/// a cache of parse results.
+	pub fn structural_hash(&self) -> u64 {
+		let mut hasher = StructuralHasher::new();
+		hasher.expression(self);
+		hasher.finish()
+	}
+}
+
+/// A hash of a whole program, as computed for each expression by
+/// [`Expression::structural_hash`].
+pub fn structural_hash(program: &[Expression]) -> u64 {
+	let mut hasher = StructuralHasher::new();
+	hasher.usize(program.len());
+	for expression in program {
+		hasher.expression(expression);
+	}
+	hasher.finish()
+}
+
+/// 64-bit FNV-1a, which unlike the standard library's hashers is guaranteed to stay the
+/// same. Each variable length part is prefixed with its length, so that moving content
+/// from one part to the next changes the hash.
+struct StructuralHasher(u64);
+
+impl StructuralHasher {
+	const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+	const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+	fn new() -> Self {
+		Self(Self::OFFSET_BASIS)
+	}
+
+	fn finish(&self) -> u64 {
+		self.0
+	}
+
+	fn bytes(&mut self, bytes: &[u8]) {
+		for &byte in bytes {
+			self.0 ^= byte as u64;
+			self.0 = self.0.wrapping_mul(Self::PRIME);
+		}
+	}
+
+	fn tag(&mut self, tag: u8) {
+		self.bytes(&[tag]);
+	}
+
+	fn usize(&mut self, n: usize) {
+		self.bytes(&(n as u64).to_le_bytes());
+	}
+
+	fn str(&mut self, s: &str) {
+		self.usize(s.len());
+		self.bytes(s.as_bytes());
+	}
+
+	fn expression(&mut self, expression: &Expression) {
+		self.tag(match expression.kind {
+			ExpressionKind::Block => 0,
+			ExpressionKind::List => 1,
+			ExpressionKind::Item => 2,
+		});
+		let values = || {
+			expression
+				.values
+				.iter()
+				.filter(|value| !matches!(value.node, Phrase::Comment(_)))
+		};
+		self.usize(values().count());
+		for value in values() {
+			self.phrase(value);
+		}
+		match &expression.tail {
+			Some(tail) => {
+				self.tag(1);
+				self.phrase(tail);
+			}
+			None => self.tag(0),
+		}
+	}
+
+	fn phrase(&mut self, phrase: &Phrase) {
+		match phrase {
+			Phrase::Expression(expression) => {
+				self.tag(0);
+				self.expression(expression);
+			}
+			// Interning doesn't change what an identifier means
+			Phrase::Identifier(_) | Phrase::Symbol(_) => {
+				self.tag(1);
+				self.str(phrase.as_identifier().unwrap_or_default());
+			}
+			Phrase::Text(text) => {
+				self.tag(2);
+				self.str(text);
+			}
+			Phrase::Char(c) => {
+				self.tag(3);
+				self.bytes(&(*c as u32).to_le_bytes());
+			}
+			Phrase::Number(Number::Integer(n)) => {
+				self.tag(4);
+				self.bytes(&n.to_le_bytes());
+			}
+			Phrase::Number(Number::Float(n)) => {
+				self.tag(5);
+				self.bytes(&n.to_bits().to_le_bytes());
+			}
+			// Comments are left out of the expressions they're in
+			Phrase::Comment(_) => {}
+			Phrase::Keyword(name) => {
+				self.tag(7);
+				self.str(name);
+			}
+			Phrase::Boolean(boolean) => {
+				self.tag(8);
+				self.tag(*boolean as u8);
+			}
+			Phrase::Nil => self.tag(9),
+		}
+	}
+}
+
+#[cfg(test)]
+mod structural_hash_tests {
+	use super::*;
+	use crate::parse;
+	use crate::parser::Parser;
+
+	#[test]
+	fn ignores_spans() {
+		let a = parse("(a b)").unwrap();
+		let b = parse("\n\n  (  a\n\t b )  ").unwrap();
+		assert_ne!(a, b);
+		assert_eq!(structural_hash(&a), structural_hash(&b));
+		assert_eq!(a[0].structural_hash(), b[0].structural_hash());
+
+		let mut parser = Parser::from("(a b)").with_interning();
+		let symbols = crate::parser::parse_program(&mut parser).unwrap();
+		assert_eq!(structural_hash(&symbols), structural_hash(&a));
+	}
+
+	#[test]
+	fn reflects_contents() {
+		let hashes = [
+			"(a b)",
+			"[a b]",
+			"(a . b)",
+			"(ab)",
+			"(a) (b)",
+			"(\"a\" b)",
+			"(:a b)",
+			"(a 1)",
+			"(a 1.0)",
+		]
+		.map(|source| structural_hash(&parse(source).unwrap()));
+		for (i, hash) in hashes.iter().enumerate() {
+			assert!(!hashes[..i].contains(hash), "{}", i);
+		}
+	}
+
+	#[test]
+	fn ignores_comments() {
+		let plain = parse("(a b)").unwrap();
+		for source in [
+			"; doc\n(a b)",
+			"(a b) ; trailing",
+			"(a ; b\n b)",
+			"(a #| b |# b #; c)",
+			"#| doc |# (; start\n a b #; c ; d\n)",
+		] {
+			let commented = parse(source).unwrap();
+			assert_eq!(
+				structural_hash(&commented),
+				structural_hash(&plain),
+				"{}",
+				source
+			);
+			assert_eq!(commented[0].structural_hash(), plain[0].structural_hash());
+		}
+	}
+
+	#[test]
+	fn stable() {
+		// The hash may be stored, so it can't ever change for the same program
+		assert_eq!(structural_hash(&[]), 0xa8c7_f832_281a_39c5);
+	}
+}
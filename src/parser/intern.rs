@@ -0,0 +1,135 @@
+//! Interning of identifiers, so that a name used over and over is only stored once, and
+//! can be compared without looking at its text.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// An interned identifier. Symbols are equal exactly when their names are, which for
+/// symbols from the same [`Interner`] only takes checking that they share a name.
+#[derive(Clone, Debug)]
+pub struct Symbol {
+	id: u32,
+	name: Arc<str>,
+}
+
+impl Symbol {
+	/// Where the symbol's name is kept in the interner it came from.
+	pub fn id(&self) -> u32 {
+		self.id
+	}
+
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+}
+
+impl PartialEq for Symbol {
+	fn eq(&self, other: &Self) -> bool {
+		// Ids are only unique within one interner, so they can't be compared on their own
+		Arc::ptr_eq(&self.name, &other.name) || self.name == other.name
+	}
+}
+
+impl Eq for Symbol {}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Symbol {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.name)
+	}
+}
+
+/// Hands out a [`Symbol`] for each distinct name, and the same one again whenever a name
+/// comes up a second time.
+#[derive(Clone, Debug, Default)]
+pub struct Interner {
+	ids: HashMap<Arc<str>, u32>,
+	names: Vec<Arc<str>>,
+}
+
+impl Interner {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn intern(&mut self, name: &str) -> Symbol {
+		if let Some((name, &id)) = self.ids.get_key_value(name) {
+			return Symbol {
+				id,
+				name: name.clone(),
+			};
+		}
+
+		let id = self.names.len() as u32;
+		let name: Arc<str> = Arc::from(name);
+		self.ids.insert(name.clone(), id);
+		self.names.push(name.clone());
+		Symbol { id, name }
+	}
+
+	/// The name of the symbol with the given id, if this interner handed it out.
+	pub fn resolve(&self, id: u32) -> Option<&str> {
+		self.names.get(id as usize).map(|name| &**name)
+	}
+
+	/// How many distinct names have been interned.
+	pub fn len(&self) -> usize {
+		self.names.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.names.is_empty()
+	}
+}
+
+#[cfg(test)]
+mod interner_tests {
+	use super::*;
+	use crate::parser::parse_program;
+	use crate::parser::Parser;
+	use crate::parser::Phrase;
+
+	#[test]
+	fn same_symbol() {
+		let mut interner = Interner::new();
+		let foo = interner.intern("foo");
+		let bar = interner.intern("bar");
+		assert_eq!(interner.intern("foo"), foo);
+		assert_ne!(foo, bar);
+		assert_eq!(interner.len(), 2);
+		assert_eq!(interner.resolve(bar.id()), Some("bar"));
+	}
+
+	#[test]
+	fn separate_interners() {
+		let (mut first, mut second) = (Interner::new(), Interner::new());
+		assert_eq!(first.intern("a").id(), second.intern("b").id());
+		assert_ne!(first.intern("a"), second.intern("b"));
+		assert_eq!(first.intern("a"), second.intern("a"));
+
+		let a = parse_program(&mut Parser::from("(a)").with_interning()).unwrap();
+		let b = parse_program(&mut Parser::from("(b)").with_interning()).unwrap();
+		assert_ne!(a, b);
+	}
+
+	#[test]
+	fn parse() {
+		let mut parser = Parser::from("(foo bar (foo))").with_interning();
+		let program = parse_program(&mut parser).unwrap();
+		let values = &program[0].values;
+		let (Phrase::Symbol(first), Phrase::Expression(nested)) =
+			(&values[0].node, &values[2].node)
+		else {
+			panic!("expected symbols, got {:?}", values);
+		};
+		let Phrase::Symbol(second) = &nested.values[0].node else {
+			panic!("expected a symbol, got {:?}", nested.values);
+		};
+
+		assert_eq!(first.id(), second.id());
+		assert_eq!(second.name(), "foo");
+		assert_eq!(parser.interner().map(Interner::len), Some(2));
+		// Printing a symbol is just like printing the identifier it stands for
+		assert_eq!(program[0].to_string(), "(foo bar (foo))");
+	}
+}
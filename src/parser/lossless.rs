@@ -0,0 +1,292 @@
+//! A syntax tree which keeps every byte of the source, for tools like formatters that
+//! should only change what they mean to. Each node records the whitespace before it and
+//! its own text exactly as it was written, so printing the tree back out reproduces the
+//! source it was parsed from.
+
+use std::fmt;
+use std::fmt::Display;
+
+use super::token::tokenize_program;
+use super::ExpressionKind;
+use super::ParseError;
+use super::Span;
+use super::Token;
+use super::TokenKind;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LosslessProgram {
+	pub nodes: Vec<LosslessNode>,
+	/// Whatever whitespace follows the last node.
+	pub trailing_trivia: String,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LosslessNode {
+	/// A single token, like an identifier, a comment, a `'` quoting shorthand, or a
+	/// `::type`. Types and quotes are kept as tokens of their own, next to whatever
+	/// they apply to.
+	Token {
+		leading_trivia: String,
+		token: Token,
+		text: String,
+	},
+	Expression {
+		leading_trivia: String,
+		kind: ExpressionKind,
+		values: Vec<LosslessNode>,
+		/// Whatever whitespace comes between the last value and the closing bracket.
+		closing_trivia: String,
+		span: Span,
+	},
+}
+
+impl Display for LosslessProgram {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for node in &self.nodes {
+			write!(f, "{}", node)?;
+		}
+		write!(f, "{}", self.trailing_trivia)
+	}
+}
+
+impl Display for LosslessNode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Token {
+				leading_trivia,
+				text,
+				..
+			} => write!(f, "{}{}", leading_trivia, text),
+			Self::Expression {
+				leading_trivia,
+				kind,
+				values,
+				closing_trivia,
+				..
+			} => {
+				write!(f, "{}{}", leading_trivia, kind.initiator())?;
+				for value in values {
+					write!(f, "{}", value)?;
+				}
+				write!(f, "{}{}", closing_trivia, kind.terminator())
+			}
+		}
+	}
+}
+
+/// An expression that has been opened, but not yet closed.
+struct Open {
+	leading_trivia: String,
+	kind: ExpressionKind,
+	values: Vec<LosslessNode>,
+	span: Span,
+}
+
+/// Parses `source` into a tree that can be printed back out exactly as it was. The
+/// source has to be a valid program, and fails to parse in exactly the same way as it
+/// would for `parse_tokens`.
+pub fn parse_lossless_program(source: &str) -> Result<LosslessProgram, ParseError> {
+	// The program is checked while it's split up, so that the tree can be built without
+	// worrying about anything being unbalanced
+	let tokens = tokenize_program(source)?;
+
+	let mut nodes = vec![];
+	let mut open: Vec<Open> = vec![];
+	// Everything before `cursor` already belongs to a node. Offsets count any byte order
+	// mark, so it ends up as part of the first node's trivia.
+	let mut cursor = 0;
+
+	for token in tokens {
+		let leading_trivia = source[cursor..token.span.start.offset].to_string();
+		let text = source[token.span.bytes()].to_string();
+		cursor = token.span.end.offset;
+
+		if let Some(kind) = token.kind.opens() {
+			open.push(Open {
+				leading_trivia,
+				kind,
+				values: vec![],
+				span: token.span,
+			});
+			continue;
+		}
+
+		let closed = match token.kind.closes() {
+			Some(_) => open.pop(),
+			None => None,
+		};
+		let node = match closed {
+			Some(expression) => LosslessNode::Expression {
+				leading_trivia: expression.leading_trivia,
+				kind: expression.kind,
+				values: expression.values,
+				closing_trivia: leading_trivia,
+				span: Span::new(expression.span.start, token.span.end),
+			},
+			None if token.kind == TokenKind::Eof => {
+				return Ok(LosslessProgram {
+					nodes,
+					trailing_trivia: leading_trivia,
+				});
+			}
+			None => LosslessNode::Token {
+				leading_trivia,
+				token,
+				text,
+			},
+		};
+
+		match open.last_mut() {
+			Some(parent) => parent.values.push(node),
+			None => nodes.push(node),
+		}
+	}
+
+	// Tokens always end with an `Eof`, which returns above
+	unreachable!("tokens should end with an Eof")
+}
+
+/// Merges each run of `;` comments on consecutive lines into a single block of
+/// documentation, along with the span it covers. The `;` and a single space after it
+/// are stripped from each line, so that
+///
+/// ```text
+/// ; Adds two numbers
+/// ;   together
+/// ```
+///
+/// becomes `"Adds two numbers\n  together"`. Anything else between two comments, like a
+/// blank line or an expression, splits them into separate blocks.
+pub fn group_doc_comments(program: &LosslessProgram) -> Vec<(Span, String)> {
+	let mut docs = vec![];
+	collect_doc_comments(&program.nodes, &mut docs);
+	docs
+}
+
+fn collect_doc_comments(nodes: &[LosslessNode], docs: &mut Vec<(Span, String)>) {
+	let mut run: Option<(Span, String)> = None;
+	// A comment's span includes the line break after it, so lines are compared by where
+	// each comment starts
+	let mut last_line = 0;
+	for node in nodes {
+		match node {
+			LosslessNode::Token {
+				token: Token {
+					kind: TokenKind::Comment(body),
+					span,
+				},
+				text,
+				..
+			} if text.starts_with(';') => {
+				let line = body.strip_prefix(' ').unwrap_or(body);
+				match &mut run {
+					Some((run_span, doc)) if span.start.line == last_line + 1 => {
+						doc.push('\n');
+						doc.push_str(line);
+						run_span.end = span.end;
+					}
+					_ => {
+						docs.extend(run.take());
+						run = Some((*span, line.to_string()));
+					}
+				}
+				last_line = span.start.line;
+			}
+			LosslessNode::Expression { values, .. } => {
+				docs.extend(run.take());
+				collect_doc_comments(values, docs);
+			}
+			LosslessNode::Token { .. } => docs.extend(run.take()),
+		}
+	}
+	docs.extend(run);
+}
+
+#[cfg(test)]
+mod group_doc_comments_tests {
+	use super::*;
+
+	#[test]
+	fn consecutive_lines() {
+		let source = "; Adds two numbers\n;   together\n;\n(func add (a b) (+ a b))";
+		let program = parse_lossless_program(source).unwrap();
+		let docs = group_doc_comments(&program);
+		assert_eq!(docs.len(), 1);
+		assert_eq!(docs[0].1, "Adds two numbers\n  together\n");
+		assert_eq!(
+			&source[docs[0].0.bytes()],
+			"; Adds two numbers\n;   together\n;\n"
+		);
+	}
+
+	#[test]
+	fn separate_blocks() {
+		let source = "; one\n\n; two\n(a ; three\n #| not a doc |#\n ; four\n b) ; five";
+		let program = parse_lossless_program(source).unwrap();
+		let docs = group_doc_comments(&program)
+			.into_iter()
+			.map(|(_, doc)| doc)
+			.collect::<Vec<_>>();
+		assert_eq!(docs, vec!["one", "two", "three", "four", "five"]);
+	}
+}
+
+#[cfg(test)]
+mod parse_lossless_program_tests {
+	use super::*;
+
+	#[test]
+	fn round_trip() {
+		let source = include_str!("../../tests/testdata/lossless/irregular.yall");
+		let program = parse_lossless_program(source).unwrap();
+		assert_eq!(program.to_string(), source);
+	}
+
+	#[test]
+	fn round_trip_everywhere() {
+		for source in [
+			"",
+			"  \n\t ",
+			"\u{FEFF}( a )",
+			"#!/usr/bin/env yall\n(a)",
+			"(a)\r\n(b)",
+			"; only a comment",
+			"(a::b  c::d)",
+			"( a::int  'b  `( c ,d ,@e ) . f )",
+			"#; #| a |#:b (c)",
+		] {
+			let program = parse_lossless_program(source).unwrap();
+			assert_eq!(program.to_string(), source);
+		}
+	}
+
+	#[test]
+	fn trivia() {
+		let program = parse_lossless_program("  (a  b )\n").unwrap();
+		assert_eq!(program.trailing_trivia, "\n");
+		let LosslessNode::Expression {
+			leading_trivia,
+			values,
+			closing_trivia,
+			..
+		} = &program.nodes[0]
+		else {
+			panic!("expected an expression");
+		};
+		assert_eq!(leading_trivia, "  ");
+		assert_eq!(closing_trivia, " ");
+		assert_eq!(values[1].to_string(), "  b");
+	}
+
+	#[test]
+	fn same_errors() {
+		use crate::parser::parse_tokens;
+
+		for source in ["(a", ")", "(a]", "\"a", "(a]\n(b \"c"] {
+			assert_eq!(
+				parse_lossless_program(source).unwrap_err(),
+				parse_tokens(source).unwrap_err()
+			);
+		}
+	}
+}
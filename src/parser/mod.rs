@@ -1,22 +1,121 @@
 use crate::util::peek_while::peek_while;
 use crate::util::peek_while::PeekWhile;
-use std::iter::Peekable;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::iter::FusedIterator;
 use std::str::Chars;
 
+mod borrowed;
+mod error;
 mod expression;
+mod hash;
+mod intern;
+mod lossless;
+mod number;
+mod owned;
 mod phrase;
+mod pretty;
+mod program;
+mod read;
+mod span;
+use span::display_width;
+mod token;
+pub use borrowed::parse_borrowed_program;
+pub use borrowed::BorrowedExpression;
+pub use borrowed::BorrowedParser;
+pub use borrowed::BorrowedPhrase;
+pub use borrowed::BorrowedProgram;
+pub use error::ParseError;
 pub use expression::parse_expression;
+use expression::parse_top_level_comments;
+use expression::parse_top_level_expression;
 pub use expression::Expression;
 pub use expression::ExpressionKind;
+pub use expression::Phrases;
+pub use hash::structural_hash;
+pub use intern::Interner;
+pub use intern::Symbol;
+pub use lossless::group_doc_comments;
+pub use lossless::parse_lossless_program;
+pub use lossless::LosslessNode;
+pub use lossless::LosslessProgram;
+pub use number::Number;
+pub use owned::parse_owned_program;
+pub use owned::ParsedProgram;
+pub use phrase::parse_phrase;
+pub(crate) use phrase::write_comment;
 pub use phrase::Phrase;
-use phrase::*;
+pub use phrase::PhraseKind;
+pub use phrase::QuoteKind;
+pub use program::Program;
+pub use read::parse_read_program;
+pub use read::ReadChars;
+pub use span::Location;
+pub use span::Span;
+pub use span::Spanned;
+pub use span::Utf16Location;
+pub use token::parse_tokens;
+pub use token::parse_tokens_unbounded;
+pub use token::tokenize;
+pub use token::Token;
+pub use token::TokenKind;
+
+/// Every kind of expression that has brackets, which are all enabled unless the parser is
+/// told otherwise.
+pub const ALL_BRACKETS: [ExpressionKind; 3] = [
+	ExpressionKind::Item,
+	ExpressionKind::List,
+	ExpressionKind::Block,
+];
+
+/// How deeply expressions may be nested before parsing gives up, unless the parser
+/// is told otherwise.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// Parses a phrase beginning with the character a reader macro was registered for, which
+/// is still the next character when it's called.
+pub type ReaderMacro<I> = fn(&mut Parser<I>) -> Result<Phrase, ParseError>;
+
+/// Reader macros, by the character which triggers them.
+pub type ReaderMacros<I> = HashMap<char, ReaderMacro<I>>;
+
+/// What begins a line comment, unless the parser is told otherwise.
+pub const DEFAULT_COMMENT_PREFIX: &str = ";";
+
+/// How many errors are collected when recovering from them, before giving up.
+pub const DEFAULT_MAX_ERRORS: usize = 20;
 
 pub struct Parser<I>
 where
 	I: Iterator<Item = char>,
 {
-	source: Peekable<I>,
+	source: I,
+	/// Characters which have been peeked at, but not consumed yet.
+	lookahead: VecDeque<char>,
 	location: Location,
+	depth: usize,
+	max_depth: usize,
+	/// How many nodes have been parsed so far, and how many may be.
+	nodes: usize,
+	max_nodes: Option<usize>,
+	/// How many bytes long a single string or identifier may be.
+	max_string_len: Option<usize>,
+	max_identifier_len: Option<usize>,
+	/// How many errors may be collected by [`parse_program_with_recovery`].
+	max_errors: usize,
+	/// The kinds of expressions which may be opened, by their brackets.
+	brackets: Vec<ExpressionKind>,
+	/// What begins a line comment.
+	comment_prefix: String,
+	/// Whether comments are skipped like whitespace, instead of being kept in the tree.
+	discard_comments: bool,
+	tab_width: u32,
+	/// Whether columns count how wide characters are shown, rather than how many there are.
+	#[cfg(feature = "unicode-width")]
+	display_width: bool,
+	/// Identifiers are interned as symbols when this is set.
+	interner: Option<Interner>,
+	reader_macros: ReaderMacros<I>,
 }
 
 impl<I> Iterator for Parser<I>
@@ -26,82 +125,966 @@ where
 	type Item = char;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		let next = self.source.next();
+		let next = self.lookahead.pop_front().or_else(|| self.source.next());
+		self.location.offset += next.map_or(0, char::len_utf8);
 
-		if let Some('\n') = next {
-			self.location.next_line();
-		} else {
-			self.location.next_column();
+		match next {
+			Some('\n') => self.location.next_line(),
+			// A \r\n only counts as one line break, which happens with the \n
+			Some('\r') if self.peek() == Some(&'\n') => {}
+			Some('\r') => self.location.next_line(),
+			Some('\t') => self.location.next_tab(self.tab_width),
+			#[cfg(feature = "unicode-width")]
+			Some(c) if self.display_width => self.location.next_columns(display_width(c) as u32),
+			Some(_) => self.location.next_column(),
+			// Running out doesn't move anywhere
+			None => {}
 		}
 
 		next
 	}
 }
 
+/// Some editors begin files with a byte order mark, which is never part of the program.
+const BYTE_ORDER_MARK: char = '\u{FEFF}';
+
 // Ideally these would be a single `impl` with a  `AsRef<str>` constraint, but the
 // compiler complains about `str` not being `Sized`.
 impl<'a> From<&'a str> for Parser<Chars<'a>> {
 	fn from(s: &'a str) -> Self {
-		Self {
-			source: s.chars().peekable(),
-			location: Default::default(),
-		}
+		// Offsets still count the byte order mark, so that they match up with the source
+		// as it was given
+		let (s, offset) = match s.strip_prefix(BYTE_ORDER_MARK) {
+			Some(s) => (s, BYTE_ORDER_MARK.len_utf8()),
+			None => (s, 0),
+		};
+		let mut parser = Self::new(s.chars(), offset);
+		parser.skip_shebang();
+		parser
 	}
 }
 impl<'a> From<&'a String> for Parser<Chars<'a>> {
 	fn from(s: &'a String) -> Self {
-		Self {
-			source: s.chars().peekable(),
-			location: Default::default(),
-		}
+		Self::from(s.as_str())
 	}
 }
 
 impl<I: Iterator<Item = char>> Parser<I> {
+	/// A parser with every option left at its default, which starts counting from
+	/// `offset` bytes into the source.
+	fn new(source: I, offset: usize) -> Self {
+		Self {
+			source,
+			lookahead: VecDeque::new(),
+			location: Location {
+				offset,
+				..Default::default()
+			},
+			depth: 0,
+			max_depth: DEFAULT_MAX_DEPTH,
+			nodes: 0,
+			max_nodes: None,
+			max_string_len: None,
+			max_identifier_len: None,
+			max_errors: DEFAULT_MAX_ERRORS,
+			brackets: ALL_BRACKETS.to_vec(),
+			comment_prefix: DEFAULT_COMMENT_PREFIX.to_string(),
+			discard_comments: false,
+			tab_width: 1,
+			#[cfg(feature = "unicode-width")]
+			display_width: false,
+			interner: None,
+			reader_macros: HashMap::new(),
+		}
+	}
+
+	/// The location of the next character to be consumed.
+	pub fn location(&self) -> Location {
+		self.location
+	}
+
+	/// How many bytes of source have been consumed so far.
+	pub fn offset(&self) -> usize {
+		self.location.offset
+	}
+
+	/// Consumes the next character, returning an error if it isn't `c`.
+	pub fn expect(&mut self, c: char, expected: &'static str) -> Result<(), ParseError> {
+		let location = self.location();
+		match self.next() {
+			Some(next) if next == c => Ok(()),
+			found => Err(ParseError::unexpected(found, expected, location)),
+		}
+	}
+
+	/// Sets how deeply expressions may be nested. Each level of nesting recurses, so
+	/// without a limit a long enough run of `(` would overflow the stack.
+	pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+		self.max_depth = max_depth;
+		self
+	}
+
+	/// Sets how many nodes may be parsed in total, counted the same way as
+	/// [`Expression::node_count`]. Parsing stops as soon as there would be more, so that
+	/// a huge program can't use up all of the memory before it's found to be too big.
+	pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+		self.max_nodes = Some(max_nodes);
+		self
+	}
+
+	/// Sets how many bytes long a string may be, once its escapes are worked out. Parsing
+	/// stops as soon as a string grows any longer, rather than after all of it has been
+	/// read.
+	pub fn with_max_string_len(mut self, max_string_len: usize) -> Self {
+		self.max_string_len = Some(max_string_len);
+		self
+	}
+
+	/// Sets how many bytes long an identifier or keyword may be, stopping as soon as one
+	/// grows any longer, just like [`Parser::with_max_string_len`].
+	pub fn with_max_identifier_len(mut self, max_identifier_len: usize) -> Self {
+		self.max_identifier_len = Some(max_identifier_len);
+		self
+	}
+
+	/// Sets how many errors [`parse_program_with_recovery`] collects before it stops
+	/// parsing, so that a badly broken program doesn't bury the first few errors, which
+	/// are usually the ones that matter, under thousands of others.
+	pub fn with_max_errors(mut self, max_errors: usize) -> Self {
+		self.max_errors = max_errors;
+		self
+	}
+
+	/// Sets which kinds of expressions may be opened. Opening any other kind is an error,
+	/// so that `[a]` can't be parsed at all when only items are enabled.
+	pub fn with_brackets(mut self, brackets: &[ExpressionKind]) -> Self {
+		self.brackets = brackets.to_vec();
+		self
+	}
+
+	/// Sets what begins a line comment, like `#` or `//`. The prefix wins out over
+	/// anything else it could be the start of, so with `//`, an operator like `//=` would
+	/// be a comment instead, while `/` on its own is still an operator. An empty prefix
+	/// turns line comments off entirely.
+	pub fn with_comment_prefix(mut self, prefix: &str) -> Self {
+		self.comment_prefix = prefix.to_string();
+		self
+	}
+
+	/// Whether a line comment begins at the next character.
+	pub(crate) fn at_comment(&mut self) -> bool {
+		let prefix = std::mem::take(&mut self.comment_prefix);
+		let at_comment = !prefix.is_empty()
+			&& prefix
+				.chars()
+				.enumerate()
+				.all(|(i, c)| self.peek_nth(i) == Some(&c));
+		self.comment_prefix = prefix;
+		at_comment
+	}
+
+	/// Consumes the prefix of a line comment, which should be next.
+	pub(crate) fn skip_comment_prefix(&mut self) {
+		for _ in 0..self.comment_prefix.chars().count() {
+			self.next();
+		}
+	}
+
+	/// Skips over comments just like whitespace when `discard_comments` is set, so that
+	/// the tree never holds any [`Phrase::Comment`]s, nor any leading comments.
+	pub fn with_discard_comments(mut self, discard_comments: bool) -> Self {
+		self.discard_comments = discard_comments;
+		self
+	}
+
+	/// Sets how many columns apart tab stops are, so that columns line up with how an
+	/// editor would show the source. By default a tab is only one column wide.
+	pub fn with_tab_width(mut self, tab_width: u32) -> Self {
+		self.tab_width = tab_width;
+		self
+	}
+
+	/// Counts columns by how wide characters are shown in a terminal, so that full width
+	/// characters, like most CJK, take up two columns, and combining marks take up none.
+	/// By default every character but a tab is one column wide.
+	#[cfg(feature = "unicode-width")]
+	pub fn with_display_width(mut self) -> Self {
+		self.display_width = true;
+		self
+	}
+
+	/// Interns every identifier that gets parsed, so that they come out as
+	/// [`Phrase::Symbol`]s rather than [`Phrase::Identifier`]s. Each name is only stored
+	/// once, no matter how often it's used, and the names can be looked up afterwards
+	/// with [`Parser::interner`].
+	pub fn with_interning(mut self) -> Self {
+		self.interner = Some(Interner::new());
+		self
+	}
+
+	/// Registers a reader macro, which parses any phrase beginning with `trigger` instead
+	/// of the parser itself. Macros are consulted before anything built in, so they can
+	/// take over syntax that already means something, and the built in parsers, like
+	/// [`parse_phrase`], can be used as macros too, or be called from them to parse
+	/// whatever comes after the trigger.
+	pub fn with_reader_macro(mut self, trigger: char, reader_macro: ReaderMacro<I>) -> Self {
+		self.reader_macros.insert(trigger, reader_macro);
+		self
+	}
+
+	/// The reader macro to parse a phrase beginning with `c`, if there is one.
+	pub(crate) fn reader_macro(&self, c: char) -> Option<ReaderMacro<I>> {
+		self.reader_macros.get(&c).copied()
+	}
+
+	/// The interner holding every symbol parsed so far, if interning is enabled.
+	pub fn interner(&self) -> Option<&Interner> {
+		self.interner.as_ref()
+	}
+
+	/// Wraps up a parsed identifier, interning it if that's enabled.
+	pub(crate) fn identifier(&mut self, name: String) -> Phrase {
+		match &mut self.interner {
+			Some(interner) => Phrase::Symbol(interner.intern(&name)),
+			None => Phrase::Identifier(name),
+		}
+	}
+
+	/// Skips a `#!` line at the very start of the source, so that scripts can be run
+	/// directly. The line break after it is left to be skipped as whitespace.
+	fn skip_shebang(&mut self) {
+		if self.peek() == Some(&'#') && self.peek_nth(1) == Some(&'!') {
+			while self.peek().is_some_and(|&c| c != '\n' && c != '\r') {
+				self.next();
+			}
+		}
+	}
+
+	/// Records that an expression starting at `location` has been opened.
+	pub(crate) fn enter(&mut self, location: Location) -> Result<(), ParseError> {
+		if self.depth >= self.max_depth {
+			return Err(ParseError::NestingTooDeep {
+				max_depth: self.max_depth,
+				location,
+			});
+		}
+		self.depth += 1;
+		Ok(())
+	}
+
+	/// Records that `count` more nodes, starting at `location`, are about to be parsed.
+	pub(crate) fn add_nodes(&mut self, count: usize, location: Location) -> Result<(), ParseError> {
+		self.nodes += count;
+		match self.max_nodes {
+			Some(max_nodes) if self.nodes > max_nodes => Err(ParseError::TooLarge {
+				max_nodes,
+				location,
+			}),
+			_ => Ok(()),
+		}
+	}
+
+	/// Checks that a string which began at `location` hasn't grown too long.
+	pub(crate) fn check_string_len(
+		&self,
+		text: &str,
+		location: Location,
+	) -> Result<(), ParseError> {
+		check_len("string", text, self.max_string_len, location)
+	}
+
+	/// Checks that an identifier which began at `location` hasn't grown too long.
+	pub(crate) fn check_identifier_len(
+		&self,
+		identifier: &str,
+		location: Location,
+	) -> Result<(), ParseError> {
+		check_len("identifier", identifier, self.max_identifier_len, location)
+	}
+
+	/// Checks that an expression of `kind`, opened at `location`, is allowed to be.
+	pub(crate) fn check_brackets(
+		&self,
+		kind: &ExpressionKind,
+		location: Location,
+	) -> Result<(), ParseError> {
+		match self.brackets.contains(kind) {
+			true => Ok(()),
+			false => Err(ParseError::DisabledBrackets {
+				found: kind.initiator(),
+				location,
+			}),
+		}
+	}
+
+	/// Records that the innermost open expression has been closed.
+	pub(crate) fn exit(&mut self) {
+		self.depth -= 1;
+	}
+
+	/// Skips ahead to the next opening bracket at the start of a line, which is the
+	/// most likely place for a new top-level expression to begin after an error.
+	pub(crate) fn recover(&mut self) {
+		self.depth = 0;
+		// Always move forward by at least one character, so that an error can't be
+		// found at the same spot over and over again.
+		self.next();
+		while let Some(&c) = self.peek() {
+			if self.location.column == 0 && ExpressionKind::from_initiator(c).is_some() {
+				break;
+			}
+			self.next();
+		}
+	}
+
 	pub fn peek(&mut self) -> Option<&char> {
-		self.source.peek()
+		self.peek_nth(0)
 	}
 
-	pub fn peek_while<P>(&mut self, pred: P) -> PeekWhile<I, P>
+	/// Looks `n` characters past the next one, without consuming anything.
+	pub fn peek_nth(&mut self, n: usize) -> Option<&char> {
+		while self.lookahead.len() <= n {
+			self.lookahead.push_back(self.source.next()?);
+		}
+		self.lookahead.get(n)
+	}
+
+	pub fn peek_while<P>(&mut self, pred: P) -> PeekWhile<'_, I, P>
 	where
 		P: FnMut(&char) -> bool,
 	{
-		peek_while(&mut self.source, pred)
+		peek_while(self, pred)
+	}
+}
+
+fn check_len(
+	kind: &'static str,
+	token: &str,
+	max_len: Option<usize>,
+	location: Location,
+) -> Result<(), ParseError> {
+	match max_len {
+		Some(max_len) if token.len() > max_len => Err(ParseError::TokenTooLong {
+			kind,
+			max_len,
+			location,
+		}),
+		_ => Ok(()),
+	}
+}
+
+/// Yields each top-level expression of a program as it is parsed. Parsing stops after the
+/// first error.
+pub struct Expressions<'a, I>
+where
+	I: Iterator<Item = char>,
+{
+	parser: &'a mut Parser<I>,
+	failed: bool,
+	finished: bool,
+	/// Whether an expression has been parsed yet.
+	trailing: bool,
+	trailing_comments: Vec<String>,
+}
+
+impl<I> Expressions<'_, I>
+where
+	I: Iterator<Item = char>,
+{
+	/// Whether every expression has been yielded, and the end of the source was reached
+	/// without any errors. Once iteration stops, this tells a clean end apart from
+	/// stopping after an error.
+	pub fn is_finished(&self) -> bool {
+		self.finished
+	}
+
+	/// The bodies of the comments after the last expression, once iteration has reached
+	/// the end of the source. See [`Program::trailing_comments`].
+	pub fn trailing_comments(&self) -> &[String] {
+		&self.trailing_comments
+	}
+}
+
+impl<'a, I> Iterator for Expressions<'a, I>
+where
+	I: Iterator<Item = char>,
+{
+	type Item = Result<Expression, ParseError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.failed || self.finished {
+			return None;
+		}
+
+		let comments = match parse_top_level_comments(self.parser) {
+			Ok(comments) => comments,
+			Err(error) => {
+				self.failed = true;
+				return Some(Err(error));
+			}
+		};
+
+		if self.parser.peek().is_none() {
+			self.trailing_comments = comments;
+			self.finished = true;
+			return None;
+		}
+		let expression = parse_top_level_expression(self.parser, self.trailing, comments);
+		self.failed = expression.is_err();
+		self.trailing = true;
+		Some(expression)
+	}
+}
+
+impl<I> FusedIterator for Expressions<'_, I> where I: Iterator<Item = char> {}
+
+impl<I: Iterator<Item = char>> Parser<I> {
+	pub fn expressions(&mut self) -> Expressions<'_, I> {
+		Expressions {
+			parser: self,
+			failed: false,
+			finished: false,
+			trailing: false,
+			trailing_comments: vec![],
+		}
 	}
 }
 
-struct Location {
-	line: isize,
-	column: isize,
+pub fn parse_program(s: &mut Parser<impl Iterator<Item = char>>) -> Result<Program, ParseError> {
+	let mut expressions = s.expressions();
+	let program = (&mut expressions).collect::<Result<Vec<_>, _>>()?;
+	Ok(Program::new(program, expressions.trailing_comments))
 }
 
-impl Location {
-	pub fn next_column(&mut self) {
-		self.column += 1;
+/// Everything that could be parsed from a program, along with every error found in
+/// the parts that couldn't.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ParseResult {
+	pub program: Program,
+	pub errors: Vec<ParseError>,
+}
+
+/// Parses a program without stopping at the first error. Whenever an expression fails
+/// to parse, parsing picks back up at the next one that starts a line.
+///
+/// Once more errors turn up than [`Parser::with_max_errors`] allows, parsing stops, and
+/// a [`ParseError::TooManyErrors`] is reported in place of the first error past the
+/// limit. The program then only holds the expressions that came before that error.
+pub fn parse_program_with_recovery(s: &mut Parser<impl Iterator<Item = char>>) -> ParseResult {
+	let mut result = ParseResult::default();
+	let mut expressions = s.expressions();
+	while let Some(expression) = expressions.next() {
+		match expression {
+			Ok(expression) => result.program.push(expression),
+			Err(error) if result.errors.len() >= expressions.parser.max_errors => {
+				result.errors.push(ParseError::TooManyErrors {
+					max_errors: expressions.parser.max_errors,
+					location: error.location(),
+				});
+				break;
+			}
+			Err(error) => {
+				result.errors.push(error);
+				expressions.parser.recover();
+				expressions.failed = false;
+			}
+		}
 	}
+	result.program.trailing_comments = expressions.trailing_comments;
+	result
+}
 
-	pub fn next_line(&mut self) {
-		self.line += 1;
-		self.column = 1;
+#[cfg(test)]
+mod expressions_tests {
+	use super::*;
+
+	#[test]
+	fn each_expression() {
+		let source = "(a) (b)";
+		let mut parser: Parser<_> = source.into();
+		let mut expressions = parser.expressions();
+
+		assert_eq!(
+			expressions.next().unwrap().unwrap().kind,
+			ExpressionKind::Item
+		);
+		assert_eq!(
+			expressions.next().unwrap().unwrap().kind,
+			ExpressionKind::Item
+		);
+		assert!(!expressions.is_finished());
+		assert!(expressions.next().is_none());
+		assert!(expressions.is_finished());
+		assert!(expressions.next().is_none());
+
+		let mut parser: Parser<_> = "; nothing".into();
+		let mut expressions = parser.expressions();
+		assert!(expressions.next().is_none());
+		assert!(expressions.is_finished());
+	}
+
+	#[test]
+	fn empty_programs() {
+		for (source, comments) in [
+			("", &[][..]),
+			(" \n\t ", &[]),
+			("; only a comment\n", &[" only a comment"]),
+			("#| a block |# ; and a line", &[" a block ", " and a line"]),
+		] {
+			let program = Program::new(vec![], comments.iter().map(|c| c.to_string()).collect());
+			assert_eq!(parse_program(&mut source.into()), Ok(program.clone()));
+			assert_eq!(crate::parse(source), Ok(program.clone()));
+			assert_eq!(
+				crate::parse_borrowed(source).map(BorrowedProgram::into_owned),
+				Ok(program)
+			);
+		}
+
+		// Comments after an expression are kept the same way
+		let program = parse_program(&mut "(a) ; b".into()).unwrap();
+		assert_eq!(program.len(), 1);
+		assert_eq!(program.trailing_comments, [" b"]);
+	}
+
+	#[test]
+	fn stops_after_error() {
+		let source = "(a) b (c)";
+		let mut parser: Parser<_> = source.into();
+		let mut expressions = parser.expressions();
+
+		assert!(expressions.next().unwrap().is_ok());
+		assert!(expressions.next().unwrap().is_err());
+		assert!(expressions.next().is_none());
+		assert!(!expressions.is_finished());
+
+		// Running out in the middle of an expression isn't a clean end either
+		let mut parser: Parser<_> = "(a) (b".into();
+		let mut expressions = parser.expressions();
+		assert!(expressions.next().unwrap().is_ok());
+		assert!(expressions.next().unwrap().unwrap_err().is_eof());
+		assert!(expressions.next().is_none());
+		assert!(!expressions.is_finished());
 	}
 }
 
-impl Default for Location {
-	fn default() -> Self {
-		Self { line: 1, column: 1 }
+#[cfg(test)]
+mod parse_program_with_recovery_tests {
+	use super::*;
+
+	#[test]
+	fn every_error() {
+		let source = "(a)\n(b 'cd')\n(d)\n[e f)\n(g)";
+		let result = parse_program_with_recovery(&mut source.into());
+
+		assert_eq!(result.program.len(), 3);
+		assert!(matches!(
+			result.errors.as_slice(),
+			[
+				ParseError::MultipleCharacters { .. },
+				ParseError::MismatchedDelimiter {
+					opened: '[',
+					closed: ')',
+					..
+				},
+			]
+		));
+	}
+
+	#[test]
+	fn too_many_errors() {
+		let source = "(a)\n]\n".repeat(100);
+		let result = parse_program_with_recovery(&mut Parser::from(&source).with_max_errors(5));
+
+		assert_eq!(result.errors.len(), 6);
+		assert!(result.errors[..5]
+			.iter()
+			.all(|error| matches!(error, ParseError::UnmatchedDelimiter { .. })));
+		assert!(matches!(
+			result.errors[5],
+			ParseError::TooManyErrors { max_errors: 5, .. }
+		));
+		// Each error came after one of the expressions, and nothing after the one past
+		// the limit was parsed
+		assert_eq!(result.program.len(), 6);
+		assert_eq!(result.errors[5].location().line, 11);
+
+		let result = parse_program_with_recovery(&mut source.as_str().into());
+		assert_eq!(result.errors.len(), DEFAULT_MAX_ERRORS + 1);
+	}
+
+	#[test]
+	fn exactly_max_errors() {
+		let source = "(a)\n]\n".repeat(5) + "(b)\n";
+		let result = parse_program_with_recovery(&mut Parser::from(&source).with_max_errors(5));
+
+		assert_eq!(result.errors.len(), 5);
+		assert!(result
+			.errors
+			.iter()
+			.all(|error| matches!(error, ParseError::UnmatchedDelimiter { .. })));
+		assert_eq!(result.program.len(), 6);
+	}
+
+	#[test]
+	fn no_errors() {
+		let source = "(a) (b)";
+		let result = parse_program_with_recovery(&mut source.into());
+		assert_eq!(result.program.len(), 2);
+		assert!(result.errors.is_empty());
+	}
+}
+
+#[cfg(test)]
+mod brackets_tests {
+	use super::*;
+
+	#[test]
+	fn disabled() {
+		let only_items = || Parser::from("(a)\n[a]").with_brackets(&[ExpressionKind::Item]);
+		assert_eq!(
+			parse_program(&mut only_items()),
+			Err(ParseError::DisabledBrackets {
+				found: '[',
+				location: Location {
+					line: 1,
+					column: 0,
+					offset: 4,
+				},
+			})
+		);
+
+		// Expressions that are still enabled parse just like they always would
+		let mut parser = only_items();
+		let item = parser.expressions().next().unwrap().unwrap();
+		assert_eq!(item, parse_expression(&mut "(a)".into()).unwrap());
+	}
+
+	#[test]
+	fn nested() {
+		let mut parser = Parser::from("(a {b})").with_brackets(&[ExpressionKind::Item]);
+		assert!(matches!(
+			parse_program(&mut parser),
+			Err(ParseError::DisabledBrackets { found: '{', .. })
+		));
+	}
+
+	#[test]
+	fn all_enabled() {
+		let source = "(a [b {c}])";
+		assert_eq!(
+			parse_program(&mut Parser::from(source).with_brackets(&ALL_BRACKETS)),
+			parse_program(&mut source.into())
+		);
 	}
 }
 
-pub fn parse_program(
-	s: &mut Parser<impl Iterator<Item = char>>,
-) -> miette::Result<Vec<Expression>> {
-	let mut program = Vec::new();
-	parse_whitespace(s)?;
+#[cfg(test)]
+mod max_nodes_tests {
+	use super::*;
 
-	while s.peek().is_some() {
-		program.push(parse_expression(s)?);
-		parse_whitespace(s)?;
+	#[test]
+	fn within_limit() {
+		let source = "(a b c) (d e)";
+		let program = parse_program(&mut Parser::from(source).with_max_nodes(7)).unwrap();
+		assert_eq!(crate::node_count(&program), 7);
 	}
 
-	Ok(program)
+	#[test]
+	fn too_large() {
+		let source = "(a b c) (d e)";
+		assert_eq!(
+			parse_program(&mut Parser::from(source).with_max_nodes(6)),
+			Err(ParseError::TooLarge {
+				max_nodes: 6,
+				location: Location {
+					line: 0,
+					column: 11,
+					offset: 11,
+				},
+			})
+		);
+	}
+
+	#[test]
+	fn stops_early() {
+		// Parsing gives up at the first node past the limit, long before reaching the
+		// end of the program
+		let source = format!("({})", "a ".repeat(100_000));
+		let error = parse_program(&mut Parser::from(&source).with_max_nodes(10)).unwrap_err();
+		assert_eq!(error.location().offset, 19);
+	}
+}
+
+#[cfg(test)]
+mod max_len_tests {
+	use super::*;
+	use std::io::BufReader;
+	use std::io::Read;
+
+	fn too_long(kind: &'static str, max_len: usize, column: u32) -> ParseError {
+		ParseError::TokenTooLong {
+			kind,
+			max_len,
+			location: Location {
+				line: 0,
+				column,
+				offset: column as usize,
+			},
+		}
+	}
+
+	#[test]
+	fn strings() {
+		let source = r#"("abc" "a\nb" r"abc")"#;
+		let parse = |max_len| parse_program(&mut Parser::from(source).with_max_string_len(max_len));
+		assert!(parse(3).is_ok());
+		assert_eq!(parse(2), Err(too_long("string", 2, 1)));
+
+		let mut parser = Parser::from(r#"(r"abcd")"#).with_max_string_len(3);
+		assert_eq!(parse_program(&mut parser), Err(too_long("string", 3, 1)));
+
+		// Only whole strings count, not what their escapes look like
+		let source = r#"("a\nb" "abcd")"#;
+		let mut parser = Parser::from(source).with_max_string_len(3);
+		assert_eq!(parse_program(&mut parser), Err(too_long("string", 3, 8)));
+	}
+
+	#[test]
+	fn identifiers() {
+		let source = "(abc :key |a b| +++ abcd)";
+		let parse =
+			|max_len| parse_program(&mut Parser::from(source).with_max_identifier_len(max_len));
+		assert!(parse(4).is_ok());
+		assert_eq!(parse(3), Err(too_long("identifier", 3, 20)));
+		assert_eq!(parse(2), Err(too_long("identifier", 2, 1)));
+
+		// Strings have a limit of their own
+		let mut parser = Parser::from("(\"abcd\")").with_max_identifier_len(1);
+		assert!(parse_program(&mut parser).is_ok());
+	}
+
+	#[test]
+	fn stops_early() {
+		// A string that never ends is given up on as soon as it's too long, rather than
+		// being read forever
+		let reader = BufReader::new(b"(\"".chain(std::io::repeat(b'a')));
+		let mut chars = ReadChars::new(reader);
+		let mut parser = Parser::from(&mut chars).with_max_string_len(1000);
+		assert_eq!(parse_program(&mut parser), Err(too_long("string", 1000, 1)));
+
+		let reader = BufReader::new(b"(".chain(std::io::repeat(b'a')));
+		let mut chars = ReadChars::new(reader);
+		let mut parser = Parser::from(&mut chars).with_max_identifier_len(1000);
+		assert_eq!(
+			parse_program(&mut parser),
+			Err(too_long("identifier", 1000, 1))
+		);
+	}
+}
+
+#[cfg(test)]
+mod location_tests {
+	use super::phrase::parse_whitespace;
+	use super::*;
+
+	#[test]
+	fn new_lines() {
+		let source = "a\n\nb";
+		let mut parser: Parser<_> = source.into();
+		assert_eq!(parser.next(), Some('a'));
+		parse_whitespace(&mut parser).unwrap();
+
+		assert_eq!(parser.peek(), Some(&'b'));
+		assert_eq!(
+			parser.location(),
+			Location {
+				line: 2,
+				column: 0,
+				offset: 3,
+			}
+		);
+	}
+
+	#[test]
+	fn carriage_returns() {
+		let source = "; hi\r\n(x)";
+		let program = parse_program(&mut source.into()).unwrap();
+		assert_eq!(program[0].leading_comments, vec![" hi".to_string()]);
+		assert_eq!(
+			program[0].span.start,
+			Location {
+				line: 1,
+				column: 0,
+				offset: 6,
+			}
+		);
+
+		let source = "a\rb\r\n\r\nc";
+		let mut parser: Parser<_> = source.into();
+		assert_eq!(parser.next(), Some('a'));
+		assert_eq!(parser.next(), Some('\r'));
+		assert_eq!(
+			parser.location(),
+			Location {
+				line: 1,
+				column: 0,
+				offset: 2,
+			}
+		);
+		assert_eq!(parser.next(), Some('b'));
+		parse_whitespace(&mut parser).unwrap();
+		assert_eq!(
+			parser.location(),
+			Location {
+				line: 3,
+				column: 0,
+				offset: 7,
+			}
+		);
+	}
+
+	#[test]
+	fn peek_nth() {
+		let source = "abc";
+		let mut parser: Parser<_> = source.into();
+		assert_eq!(parser.peek_nth(2), Some(&'c'));
+		assert_eq!(parser.peek_nth(3), None);
+		assert_eq!(parser.next(), Some('a'));
+		assert_eq!(parser.peek(), Some(&'b'));
+		assert_eq!(
+			parser.location(),
+			Location {
+				line: 0,
+				column: 1,
+				offset: 1
+			}
+		);
+	}
+
+	#[test]
+	fn offsets() {
+		let source = "é(x)";
+		let mut parser: Parser<_> = source.into();
+		assert_eq!(parser.next(), Some('é'));
+		let expression = parse_expression(&mut parser).unwrap();
+		assert_eq!(expression.span.start.column, 1);
+		assert_eq!(expression.span.bytes(), 2..5);
+		assert_eq!(&source[expression.span.bytes()], "(x)");
+	}
+
+	#[test]
+	fn byte_order_mark_offsets() {
+		let source = "\u{FEFF}(x)";
+		let program = parse_program(&mut source.into()).unwrap();
+		assert_eq!(program[0].span.start.column, 0);
+		assert_eq!(&source[program[0].span.bytes()], "(x)");
+	}
+
+	#[test]
+	fn comment_prefix() {
+		let source = "# a comment\n(a # another\n b)";
+		let mut parser = Parser::from(source).with_comment_prefix("#");
+		let program = parse_program(&mut parser).unwrap();
+		assert_eq!(
+			program,
+			parse_program(&mut "; a comment\n(a ; another\n b)".into()).unwrap()
+		);
+
+		// Anything else the prefix begins is a comment instead
+		let source = "(a //= b\n / c)";
+		let mut parser = Parser::from(source).with_comment_prefix("//");
+		let program = parse_program(&mut parser).unwrap();
+		assert_eq!(program[0].to_string(), "(a ;= b\n/ c)");
+
+		// Once it isn't the prefix, `;` is nothing special, and can't begin a phrase
+		let mut parser = Parser::from("(a ; b)").with_comment_prefix("#");
+		assert!(matches!(
+			parse_program(&mut parser),
+			Err(ParseError::UnexpectedCharacter { found: ';', .. })
+		));
+	}
+
+	#[test]
+	fn shebang() {
+		let source = "#!/usr/bin/env yall\n(x)";
+		let program = parse_program(&mut source.into()).unwrap();
+		assert_eq!(program[0].to_string(), "(x)");
+		assert_eq!(program[0].span.start.line, 1);
+		assert_eq!(&source[program[0].span.bytes()], "(x)");
+
+		let source = "\u{FEFF}#!/usr/bin/env yall\r\n(x)";
+		let program = parse_program(&mut source.into()).unwrap();
+		assert_eq!(&source[program[0].span.bytes()], "(x)");
+
+		// Anywhere but the very start, it's just an unexpected `#`
+		assert!(crate::parse("(x)\n#!/usr/bin/env yall").is_err());
+		assert!(crate::parse(" #!/usr/bin/env yall\n(x)").is_err());
+	}
+
+	#[test]
+	fn tabs() {
+		let source = "\t(a\n\t\tb c]";
+		let mut parser: Parser<_> = Parser::from(source).with_tab_width(4);
+		let error = parse_program(&mut parser).unwrap_err();
+		assert_eq!(
+			error.location(),
+			Location {
+				line: 1,
+				column: 11,
+				offset: 9,
+			}
+		);
+		assert!(error.render(source).ends_with("2 | \t\tb c]\n  | \t\t   ^"));
+
+		// Tabs only go as far as the next tab stop
+		let mut parser: Parser<_> = Parser::from("ab\tc").with_tab_width(4);
+		parser.peek_while(|&c| c != 'c').for_each(drop);
+		assert_eq!(parser.location().column, 4);
+
+		let mut parser: Parser<_> = "\t\tc".into();
+		parser.peek_while(|&c| c != 'c').for_each(drop);
+		assert_eq!(parser.location().column, 2);
+	}
+
+	#[cfg(feature = "unicode-width")]
+	#[test]
+	fn display_width() {
+		let source = "(漢字 a)\n(\"e\u{301}\" b)";
+		let program = parse_program(&mut source.into()).unwrap();
+		assert_eq!(program[0].values[1].span.start.column, 4);
+		assert_eq!(program[1].values[1].span.start.column, 6);
+
+		let program = parse_program(&mut Parser::from(source).with_display_width()).unwrap();
+		assert_eq!(program[0].values[1].span.start.column, 6);
+		assert_eq!(program[1].values[1].span.start.column, 5);
+		assert_eq!(program[0].values[1].span.start.offset, 8);
+
+		// Carets line up under wide characters too
+		let source = "(漢字 a]";
+		let error = parse_program(&mut source.into()).unwrap_err();
+		assert!(error.render(source).ends_with("\n  |        ^"));
+	}
+
+	#[test]
+	fn columns() {
+		let source = "ab";
+		let mut parser: Parser<_> = source.into();
+		assert_eq!(
+			parser.location(),
+			Location {
+				line: 0,
+				column: 0,
+				offset: 0
+			}
+		);
+		parser.next();
+		assert_eq!(
+			parser.location(),
+			Location {
+				line: 0,
+				column: 1,
+				offset: 1
+			}
+		);
+	}
 }
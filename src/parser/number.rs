@@ -0,0 +1,235 @@
+use std::fmt;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+use super::Location;
+use super::ParseError;
+
+/// The value of a number literal. Although this can hold an `f64`, it still implements `Eq`,
+/// because there is no way to write a NaN literal.
+///
+/// Literals are checked and converted as they're parsed, so a number is always valid, and
+/// always prints in the same canonical way, however it was written. `1.` and `1.0` are the
+/// same number, and both print as `1.0`. The text it was written as can still be found
+/// in the source, using the span of the phrase holding it.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum Number {
+	Integer(i64),
+	Float(f64),
+}
+
+impl Number {
+	/// Converts the text of a number literal, as scanned by `parse_number`, into its value.
+	pub fn from_literal(literal: &str, location: Location) -> Result<Self, ParseError> {
+		let (sign, unsigned) = match literal.strip_prefix('-') {
+			Some(unsigned) => ("-", unsigned),
+			None => ("", literal.strip_prefix('+').unwrap_or(literal)),
+		};
+
+		let (radix, digits) = match unsigned.get(..2) {
+			Some("0x") => (16, &unsigned[2..]),
+			Some("0o") => (8, &unsigned[2..]),
+			Some("0b") => (2, &unsigned[2..]),
+			_ => (10, unsigned),
+		};
+
+		if radix == 16 && digits.contains('p') {
+			return hex_float(digits, location).map(|float| match sign {
+				"-" => Self::Float(-float),
+				_ => Self::Float(float),
+			});
+		}
+
+		if radix == 10 && digits.contains(['.', 'e', 'E']) {
+			let float = literal
+				.parse::<f64>()
+				.or(Err(ParseError::MalformedNumber { location }))?;
+			// Too large a literal parses as infinity, rather than failing
+			return match float.is_finite() {
+				true => Ok(Self::Float(float)),
+				false => Err(ParseError::NumberOutOfRange { location }),
+			};
+		}
+
+		// Keep the sign attached while parsing, so that `i64::MIN` is still in range
+		i64::from_str_radix(&format!("{}{}", sign, digits), radix)
+			.map(Self::Integer)
+			.or(Err(ParseError::NumberOutOfRange { location }))
+	}
+}
+
+/// Converts the digits of a hex float, like the `1.8p3` of `0x1.8p3`, into its value.
+fn hex_float(digits: &str, location: Location) -> Result<f64, ParseError> {
+	let malformed = ParseError::MalformedNumber { location };
+	let (mantissa, exponent) = digits.split_once('p').ok_or(malformed.clone())?;
+	let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+	let mut value = 0.0;
+	for digit in whole.chars().chain(fraction.chars()) {
+		value = value * 16.0 + digit.to_digit(16).ok_or(malformed.clone())? as f64;
+	}
+
+	// Each digit after the point shifts the value four bits further down
+	let exponent = exponent
+		.parse::<i32>()
+		.ok()
+		.and_then(|exponent| exponent.checked_sub(4 * i32::try_from(fraction.len()).ok()?))
+		.ok_or(ParseError::NumberOutOfRange { location })?;
+	if value == 0.0 {
+		return Ok(0.0);
+	}
+	// Scaling in two steps keeps a tiny power of two from underflowing to zero before a
+	// large mantissa gets to bring it back up
+	let half = exponent / 2;
+	let value = value * 2f64.powi(half) * 2f64.powi(exponent - half);
+	match value.is_finite() {
+		true => Ok(value),
+		false => Err(ParseError::NumberOutOfRange { location }),
+	}
+}
+
+impl Display for Number {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Integer(i) => write!(f, "{}", i),
+			// `Debug` always includes a decimal point or exponent, so that the number will
+			// still be read back as a float.
+			Self::Float(float) => write!(f, "{:?}", float),
+		}
+	}
+}
+
+// Float literals can never be NaN, so comparing the bits is a total equivalence.
+impl PartialEq for Number {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Self::Integer(a), Self::Integer(b)) => a == b,
+			(Self::Float(a), Self::Float(b)) => a.to_bits() == b.to_bits(),
+			_ => false,
+		}
+	}
+}
+
+impl Eq for Number {}
+
+impl Hash for Number {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		match self {
+			Self::Integer(i) => (0, *i).hash(state),
+			Self::Float(f) => (1, f.to_bits()).hash(state),
+		}
+	}
+}
+
+#[cfg(test)]
+mod from_literal_tests {
+	use super::*;
+
+	#[test]
+	fn integers() {
+		let location = Location::default();
+		assert_eq!(
+			Number::from_literal("9223372036854775807", location),
+			Ok(Number::Integer(i64::MAX))
+		);
+		assert_eq!(
+			Number::from_literal("-9223372036854775808", location),
+			Ok(Number::Integer(i64::MIN))
+		);
+		assert_eq!(
+			Number::from_literal("-0xFF", location),
+			Ok(Number::Integer(-255))
+		);
+	}
+
+	#[test]
+	fn out_of_range() {
+		let location = Location::default();
+		let huge_hex_float = format!("0x{}p0", "f".repeat(300));
+		for out_of_range in [
+			"9223372036854775808",
+			"0x10000000000000000",
+			"1e400",
+			"-1e400",
+			"1.5e309",
+			&huge_hex_float,
+		] {
+			assert_eq!(
+				Number::from_literal(out_of_range, location),
+				Err(ParseError::NumberOutOfRange { location }),
+				"{}",
+				out_of_range
+			);
+		}
+	}
+
+	#[test]
+	fn floats() {
+		let location = Location::default();
+		assert_eq!(
+			Number::from_literal("2.5", location),
+			Ok(Number::Float(2.5))
+		);
+		assert_eq!(
+			Number::from_literal("-1e3", location),
+			Ok(Number::Float(-1000.0))
+		);
+	}
+
+	#[test]
+	fn canonical() {
+		let location = Location::default();
+		for (literal, value, canonical) in [
+			("1.", 1.0, "1.0"),
+			(".5", 0.5, "0.5"),
+			("1.0", 1.0, "1.0"),
+			("-1.", -1.0, "-1.0"),
+			("1e3", 1000.0, "1000.0"),
+		] {
+			let number = Number::from_literal(literal, location).unwrap();
+			assert_eq!(number, Number::Float(value), "{}", literal);
+			assert_eq!(number.to_string(), canonical, "{}", literal);
+		}
+	}
+
+	#[test]
+	fn original_text() {
+		let source = "(a 1. .5)";
+		let expression: crate::Expression = source.parse().unwrap();
+		let literals = expression
+			.values
+			.iter()
+			.skip(1)
+			.map(|value| &source[value.span.bytes()])
+			.collect::<Vec<_>>();
+		assert_eq!(literals, vec!["1.", ".5"]);
+	}
+
+	#[test]
+	fn hex_floats() {
+		let location = Location::default();
+		for (literal, value) in [
+			("0x1p4", 16.0),
+			("0x1.8p1", 3.0),
+			("0x.8p0", 0.5),
+			("0x1p-2", 0.25),
+			("-0xAp0", -10.0),
+			("0x0p1000", 0.0),
+			("0x1p-1074", f64::from_bits(1)),
+		] {
+			assert_eq!(
+				Number::from_literal(literal, location),
+				Ok(Number::Float(value)),
+				"{}",
+				literal
+			);
+		}
+		assert_eq!(
+			Number::from_literal("0x1p1024", location),
+			Err(ParseError::NumberOutOfRange { location })
+		);
+	}
+}
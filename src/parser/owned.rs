@@ -0,0 +1,82 @@
+//! A parsed program which holds on to its own source, so that the text of any node can
+//! be looked up from its span without needing the source passed around alongside it.
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use super::parse_tokens;
+use super::ParseError;
+use super::Program;
+use super::Span;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParsedProgram {
+	source: Arc<str>,
+	program: Program,
+}
+
+impl ParsedProgram {
+	/// The source the program was parsed from, which every span in it refers to.
+	pub fn source(&self) -> &Arc<str> {
+		&self.source
+	}
+
+	pub fn program(&self) -> &Program {
+		&self.program
+	}
+
+	/// The text of the source covered by `span`, which should come from a node of this
+	/// program. See [`Span::source_text`].
+	pub fn text(&self, span: Span) -> &str {
+		span.source_text(&self.source)
+	}
+
+	/// Gives back the source and the program separately.
+	pub fn into_parts(self) -> (Arc<str>, Program) {
+		(self.source, self.program)
+	}
+}
+
+impl Deref for ParsedProgram {
+	type Target = Program;
+
+	fn deref(&self) -> &Self::Target {
+		&self.program
+	}
+}
+
+/// Parses a complete program from `source`, keeping the source along with it.
+pub fn parse_owned_program(source: String) -> Result<ParsedProgram, ParseError> {
+	let source = Arc::<str>::from(source);
+	let program = parse_tokens(&source)?;
+	Ok(ParsedProgram { source, program })
+}
+
+#[cfg(test)]
+mod parse_owned_program_tests {
+	use super::*;
+
+	#[test]
+	fn text() {
+		let source = "; doc\n(greet \"friend\" [1 2])".to_string();
+		let program = parse_owned_program(source).unwrap();
+		assert_eq!(program.len(), 1);
+		assert_eq!(program.text(program[0].span), "(greet \"friend\" [1 2])");
+
+		let texts = program[0]
+			.values
+			.iter()
+			.map(|value| program.text(value.span))
+			.collect::<Vec<_>>();
+		assert_eq!(texts, ["greet", "\"friend\"", "[1 2]"]);
+
+		// The source outlives the program, if anything still needs it
+		let (source, expressions) = program.into_parts();
+		assert_eq!(expressions[0].values[0].source_text(&source), "greet");
+	}
+
+	#[test]
+	fn errors() {
+		assert!(parse_owned_program("(a".to_string()).is_err());
+	}
+}
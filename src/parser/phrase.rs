@@ -1,218 +1,2261 @@
-use miette::miette;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Write;
 
-use super::parse_expression;
+use super::expression::parse_delimited_expression;
 use super::Expression;
+use super::ExpressionKind;
+use super::Location;
+use super::Number;
+use super::ParseError;
 use super::Parser;
+use super::Span;
+use super::Spanned;
+use super::Symbol;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum Phrase {
 	Expression(Expression),
 	Identifier(String),
+	/// An identifier which has been interned, when parsing with
+	/// [`Parser::with_interning`].
+	Symbol(Symbol),
 	Text(String),
-	Number(String),
+	Char(char),
+	Number(Number),
 	Comment(String),
-	Label(String),
+	Keyword(String),
+	Boolean(bool),
+	Nil,
 }
 
-pub fn parse_whitespace(parser: &mut Parser<impl Iterator<Item = char>>) -> miette::Result<()> {
+/// Which variant a [`Phrase`] is, without anything it holds.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PhraseKind {
+	Expression,
+	Identifier,
+	Symbol,
+	Text,
+	Char,
+	Number,
+	Comment,
+	Keyword,
+	Boolean,
+	Nil,
+}
+
+impl Phrase {
+	pub fn kind(&self) -> PhraseKind {
+		match self {
+			Phrase::Expression(_) => PhraseKind::Expression,
+			Phrase::Identifier(_) => PhraseKind::Identifier,
+			Phrase::Symbol(_) => PhraseKind::Symbol,
+			Phrase::Text(_) => PhraseKind::Text,
+			Phrase::Char(_) => PhraseKind::Char,
+			Phrase::Number(_) => PhraseKind::Number,
+			Phrase::Comment(_) => PhraseKind::Comment,
+			Phrase::Keyword(_) => PhraseKind::Keyword,
+			Phrase::Boolean(_) => PhraseKind::Boolean,
+			Phrase::Nil => PhraseKind::Nil,
+		}
+	}
+
+	/// Whether this is a comment that will be printed as a `;` comment.
+	pub fn is_line_comment(&self) -> bool {
+		matches!(self, Phrase::Comment(body) if !body.contains('\n'))
+	}
+
+	/// The name of the identifier, whether it was interned or not.
+	pub fn as_identifier(&self) -> Option<&str> {
+		match self {
+			Phrase::Identifier(name) => Some(name),
+			Phrase::Symbol(symbol) => Some(symbol.name()),
+			_ => None,
+		}
+	}
+
+	pub fn as_text(&self) -> Option<&str> {
+		match self {
+			Phrase::Text(text) => Some(text),
+			_ => None,
+		}
+	}
+
+	pub fn as_number(&self) -> Option<Number> {
+		match self {
+			Phrase::Number(number) => Some(*number),
+			_ => None,
+		}
+	}
+
+	pub fn as_expression(&self) -> Option<&Expression> {
+		match self {
+			Phrase::Expression(expression) => Some(expression),
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod accessor_tests {
+	use super::*;
+	use crate::parse;
+	use crate::parser::Interner;
+
+	#[test]
+	fn accessors() {
+		let program = parse("(a \"b\" 1 (c) :d)").unwrap();
+		let values = program[0]
+			.values
+			.iter()
+			.map(|value| &value.node)
+			.collect::<Vec<_>>();
+
+		assert_eq!(values[0].as_identifier(), Some("a"));
+		assert_eq!(values[1].as_text(), Some("b"));
+		assert_eq!(values[2].as_number(), Some(Number::Integer(1)));
+		assert_eq!(values[3].as_expression().unwrap().to_string(), "(c)");
+
+		// Nothing is anything else, not even a keyword an identifier
+		for (i, value) in values.iter().enumerate() {
+			assert_eq!(value.as_identifier().is_some(), i == 0);
+			assert_eq!(value.as_text().is_some(), i == 1);
+			assert_eq!(value.as_number().is_some(), i == 2);
+			assert_eq!(value.as_expression().is_some(), i == 3);
+		}
+
+		let symbol = Interner::new().intern("e");
+		assert_eq!(Phrase::Symbol(symbol).as_identifier(), Some("e"));
+	}
+}
+
+#[cfg(test)]
+mod kind_tests {
+	use super::*;
+
+	#[test]
+	fn kind() {
+		assert_eq!(Phrase::Text("a".to_string()).kind(), PhraseKind::Text);
+		assert_eq!(Phrase::Nil.kind(), PhraseKind::Nil);
+
+		let expression: Expression = "(a 1 'b' :c)".parse().unwrap();
+		assert_eq!(expression.kind(), &ExpressionKind::Item);
+		assert_eq!(
+			expression.iter().map(Phrase::kind).collect::<Vec<_>>(),
+			vec![
+				PhraseKind::Identifier,
+				PhraseKind::Number,
+				PhraseKind::Char,
+				PhraseKind::Keyword
+			]
+		);
+	}
+}
+
+impl Display for Phrase {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Phrase::Expression(expression) => write!(f, "{}", expression),
+			Phrase::Identifier(identifier) => write_identifier(f, identifier),
+			Phrase::Symbol(symbol) => write_identifier(f, symbol.name()),
+			Phrase::Text(text) => {
+				f.write_char('"')?;
+				for c in text.chars() {
+					write_escaped(f, c, '"')?;
+				}
+				f.write_char('"')
+			}
+			Phrase::Char(c) => {
+				f.write_char('\'')?;
+				write_escaped(f, *c, '\'')?;
+				f.write_char('\'')
+			}
+			Phrase::Number(number) => write!(f, "{}", number),
+			Phrase::Comment(body) => write_comment(f, body),
+			Phrase::Keyword(keyword) => write!(f, ":{}", keyword),
+			Phrase::Boolean(boolean) => write!(f, "{}", boolean),
+			Phrase::Nil => write!(f, "nil"),
+		}
+	}
+}
+
+/// Writes a comment using whichever syntax can hold its body. Anything spanning
+/// multiple lines has to be a block comment.
+pub fn write_comment(f: &mut impl Write, body: &str) -> fmt::Result {
+	match body.contains('\n') {
+		true => write!(f, "#|{}|#", body),
+		false => write!(f, ";{}", body),
+	}
+}
+
+/// Writes an identifier, wrapping it in `|`s if it couldn't be parsed back otherwise.
+pub fn write_identifier(f: &mut fmt::Formatter<'_>, identifier: &str) -> fmt::Result {
+	if is_plain_identifier(identifier) {
+		return f.write_str(identifier);
+	}
+
+	f.write_char('|')?;
+	for (i, c) in identifier.chars().enumerate() {
+		// Starting with any of these would be read as an operator instead
+		if i == 0 && (c.is_whitespace() || OPERATOR_CHARACTERS.contains(&c)) {
+			write!(f, "\\u{{{:x}}}", c as u32)?;
+		} else {
+			write_escaped(f, c, '|')?;
+		}
+	}
+	f.write_char('|')
+}
+
+/// Whether `identifier` would be parsed back as itself without any `|`s around it.
+fn is_plain_identifier(identifier: &str) -> bool {
+	let mut chars = identifier.chars();
+	match chars.next() {
+		Some(c) if c.is_alphabetic() => {
+			chars.all(is_identifier_character) && !matches!(identifier, "true" | "false" | "nil")
+		}
+		Some(c) if OPERATOR_CHARACTERS.contains(&c) => {
+			chars.all(|c| OPERATOR_CHARACTERS.contains(&c))
+		}
+		_ => false,
+	}
+}
+
+/// Writes `c` as it would need to appear inside of a literal delimited by `quote`.
+fn write_escaped(f: &mut fmt::Formatter<'_>, c: char, quote: char) -> fmt::Result {
+	match c {
+		'\n' => f.write_str("\\n"),
+		'\t' => f.write_str("\\t"),
+		'\r' => f.write_str("\\r"),
+		'\0' => f.write_str("\\0"),
+		'\\' => f.write_str("\\\\"),
+		c if c == quote => write!(f, "\\{}", c),
+		c if c.is_control() => write!(f, "\\u{{{:x}}}", c as u32),
+		c => f.write_char(c),
+	}
+}
+
+pub fn parse_whitespace(parser: &mut Parser<impl Iterator<Item = char>>) -> Result<(), ParseError> {
 	while let Some(c) = parser.peek() {
 		if !c.is_whitespace() {
 			break;
 		}
-		parser.next();
+		parser.next();
+	}
+
+	Ok(())
+}
+
+/// Skips whitespace along with any `#;` datum comments, each of which comments out the
+/// whole phrase after it. Several in a row, like `#; #; a b`, comment out that many.
+pub fn skip_datum_comments(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<(), ParseError> {
+	let mut pending = 0;
+	loop {
+		parse_whitespace(parser)?;
+
+		let location = parser.location();
+		match parser.peek().copied() {
+			// Comments aren't phrases, so they don't count as what gets commented out
+			_ if parser.at_comment() => match pending {
+				0 if !parser.discard_comments => return Ok(()),
+				_ => drop(parse_comment(parser)?),
+			},
+			Some('#') if parser.peek_nth(1) == Some(&';') => {
+				parser.next();
+				parser.next();
+				pending += 1;
+			}
+			Some('#') if parser.discard_comments && parser.peek_nth(1) == Some(&'|') => {
+				drop(parse_block_comment(parser)?)
+			}
+			_ if pending == 0 => return Ok(()),
+			found @ (None | Some(')' | ']' | '}')) => {
+				return Err(ParseError::unexpected(
+					found,
+					"a phrase to comment out",
+					location,
+				));
+			}
+			Some('#') if parser.peek_nth(1) == Some(&'|') => drop(parse_block_comment(parser)?),
+			Some(_) => {
+				parse_phrase(parser)?;
+				pending -= 1;
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod skip_datum_comments_tests {
+	use super::*;
+	use crate::parser::parse_expression;
+	use crate::parser::parse_program;
+	use crate::parser::Program;
+
+	#[test]
+	fn discard_comments() {
+		let source = "; doc\n(a ; b\n #| c |# d #; e 'f) #| g |#\n(h) ; i";
+		let mut parser = Parser::from(source).with_discard_comments(true);
+		let program = parse_program(&mut parser).unwrap();
+		assert_eq!(crate::print(&program), "(a d (quote f))\n(h)\n");
+		assert!(program[0].leading_comments.is_empty());
+
+		// Nothing is left of a program of only comments
+		let mut parser = Parser::from("; a\n#| b |#").with_discard_comments(true);
+		assert_eq!(parse_program(&mut parser), Ok(Program::default()));
+
+		// They're kept by default
+		let program = parse_program(&mut source.into()).unwrap();
+		assert_eq!(program.len(), 2);
+		assert_eq!(program[0].values[1].node, Phrase::Comment(" b".to_string()));
+		assert_eq!(program.trailing_comments, [" i"]);
+	}
+
+	fn identifiers(expression: &Expression) -> Vec<&str> {
+		expression
+			.values
+			.iter()
+			.map(|value| match &value.node {
+				Phrase::Identifier(name) => name.as_str(),
+				phrase => panic!("expected an identifier, got {:?}", phrase),
+			})
+			.collect()
+	}
+
+	#[test]
+	fn item() {
+		let item = parse_expression(&mut "(a #; b c)".into()).unwrap();
+		assert_eq!(identifiers(&item), vec!["a", "c"]);
+	}
+
+	#[test]
+	fn nested() {
+		let item = parse_expression(&mut "(a #;(b (c d)) #; 'e f)".into()).unwrap();
+		assert_eq!(identifiers(&item), vec!["a", "f"]);
+	}
+
+	#[test]
+	fn stacked() {
+		let item = parse_expression(&mut "(#; #; a b c)".into()).unwrap();
+		assert_eq!(identifiers(&item), vec!["c"]);
+	}
+
+	#[test]
+	fn top_level() {
+		let program = parse_program(&mut "#; (a) (b) #; (c)".into()).unwrap();
+		assert_eq!(program.len(), 1);
+		assert_eq!(identifiers(&program[0]), vec!["b"]);
+	}
+
+	#[test]
+	fn nothing_to_comment_out() {
+		assert!(matches!(
+			parse_expression(&mut "(a #;)".into()),
+			Err(ParseError::UnexpectedCharacter {
+				found: ')',
+				expected: "a phrase to comment out",
+				..
+			})
+		));
+		assert!(matches!(
+			parse_program(&mut "(a) #;".into()),
+			Err(ParseError::UnexpectedEof { .. })
+		));
+	}
+}
+
+/// Parses a `"..."` string. Strings can span several lines, and keep any line breaks
+/// written inside of them, unless the line ends with a `\`, which joins it to the next
+/// line without a break in between.
+pub fn parse_string(parser: &mut Parser<impl Iterator<Item = char>>) -> Result<Phrase, ParseError> {
+	let location = parser.location();
+
+	// Consume "
+	parser.expect('"', "a string")?;
+
+	let mut text = String::new();
+	loop {
+		match parser.next() {
+			Some('"') => break,
+			Some('\\') if skip_line_continuation(parser) => {}
+			Some('\\') => text.push(parse_escape(parser)?),
+			Some(c) => text.push(c),
+			None => {
+				return Err(ParseError::UnterminatedString {
+					location,
+					end: parser.location(),
+				})
+			}
+		}
+		parser.check_string_len(&text, location)?;
+	}
+
+	Ok(Phrase::Text(text))
+}
+
+/// Parses a `r"..."` string, in which backslashes are just backslashes. Any number of
+/// `#`s can go between the `r` and the opening quote, like `r#"..."#`, and then the
+/// string only ends at a quote followed by that many `#`s, so that it can contain
+/// quotes of its own. Anything else starting with an `r` is just an identifier.
+pub fn parse_raw_string(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<Phrase, ParseError> {
+	let location = parser.location();
+
+	// Consume r
+	parser.expect('r', "a raw string")?;
+	if !matches!(parser.peek(), Some('"' | '#')) {
+		return finish_text_identifier(parser, "r".to_string(), location);
+	}
+
+	let hashes = parser.peek_while(|&c| c == '#').count();
+	parser.expect('"', "a raw string")?;
+
+	let mut text = String::new();
+	loop {
+		match parser.next() {
+			Some('"') => {
+				let closing = parser.peek_while(|&c| c == '#').take(hashes).count();
+				if closing == hashes {
+					break;
+				}
+				text.push('"');
+				text.extend(std::iter::repeat_n('#', closing));
+			}
+			Some(c) => text.push(c),
+			None => {
+				return Err(ParseError::UnterminatedString {
+					location,
+					end: parser.location(),
+				})
+			}
+		}
+		parser.check_string_len(&text, location)?;
+	}
+
+	Ok(Phrase::Text(text))
+}
+
+#[cfg(test)]
+mod parse_raw_string_tests {
+	use super::*;
+
+	#[test]
+	fn no_escapes() {
+		let raw = r#"r"a\nb""#;
+		assert_eq!(
+			parse_raw_string(&mut raw.into()).unwrap(),
+			Phrase::Text("a\\nb".to_string())
+		);
+	}
+
+	#[test]
+	fn hashes() {
+		let raw = r##"r#"he said "hi""#"##;
+		assert_eq!(
+			parse_raw_string(&mut raw.into()).unwrap(),
+			Phrase::Text("he said \"hi\"".to_string())
+		);
+
+		let raw = r###"r##"a "# b"##🏁"###;
+		let mut parser = raw.into();
+		assert_eq!(
+			parse_raw_string(&mut parser).unwrap(),
+			Phrase::Text("a \"# b".to_string())
+		);
+		assert_eq!(parser.next(), Some('🏁'));
+	}
+
+	#[test]
+	fn identifiers() {
+		for identifier in ["r", "raw", "r-string"] {
+			assert_eq!(
+				parse_raw_string(&mut identifier.into()).unwrap(),
+				Phrase::Identifier(identifier.to_string())
+			);
+		}
+		assert_eq!(
+			parse_raw_string(&mut "r)".into()).unwrap(),
+			Phrase::Identifier("r".to_string())
+		);
+	}
+
+	#[test]
+	fn unterminated() {
+		for unterminated in [r#"r"abc"#, r##"r#"abc""##] {
+			let error = parse_raw_string(&mut unterminated.into()).unwrap_err();
+			assert!(matches!(error, ParseError::UnterminatedString { .. }));
+			// The whole string is covered, from the `r` to the end of the source
+			assert_eq!(error.span().bytes(), 0..unterminated.len());
+		}
+	}
+}
+
+/// Skips a line break following a `\`, along with any spaces or tabs indenting the next
+/// line, returning whether there was one to skip. The `\` should already have been
+/// consumed.
+pub(super) fn skip_line_continuation(parser: &mut Parser<impl Iterator<Item = char>>) -> bool {
+	match parser.peek().copied() {
+		Some('\n') => {
+			parser.next();
+		}
+		Some('\r') if parser.peek_nth(1) == Some(&'\n') => {
+			parser.next();
+			parser.next();
+		}
+		_ => return false,
+	}
+	parser.peek_while(|&c| c == ' ' || c == '\t').for_each(drop);
+	true
+}
+
+/// Decodes an escape sequence, assuming the leading `\` has already been consumed. This
+/// is shared by strings, characters, and quoted identifiers, so that escapes mean the
+/// same thing in all of them.
+pub fn parse_escape(parser: &mut Parser<impl Iterator<Item = char>>) -> Result<char, ParseError> {
+	let location = parser.location();
+
+	match parser.next() {
+		Some('n') => Ok('\n'),
+		Some('t') => Ok('\t'),
+		Some('r') => Ok('\r'),
+		Some('0') => Ok('\0'),
+		Some('\\') => Ok('\\'),
+		Some('"') => Ok('"'),
+		Some('\'') => Ok('\''),
+		Some('x') => parse_hex_escape(parser, location),
+		Some('u') => parse_unicode_escape(parser, location),
+		Some(found) => Err(ParseError::UnknownEscape { found, location }),
+		None => Err(ParseError::UnexpectedEof {
+			expected: "an escape sequence",
+			location,
+		}),
+	}
+}
+
+/// Decodes the `HH` portion of a `\xHH` escape. Only ASCII can be written this way, so
+/// anything above `7F` is an error, rather than a single byte of some longer UTF-8
+/// encoded character, which couldn't be a character on its own.
+fn parse_hex_escape(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+	location: Location,
+) -> Result<char, ParseError> {
+	let digits = parser
+		.peek_while(|c| c.is_ascii_hexdigit())
+		.take(2)
+		.collect::<String>();
+
+	match u8::from_str_radix(&digits, 16) {
+		Ok(byte) if digits.len() == 2 && byte.is_ascii() => Ok(char::from(byte)),
+		_ => Err(ParseError::InvalidHexEscape { location }),
+	}
+}
+
+/// Decodes the rest of a `\u` escape, which is either `{HEX}`, where `HEX` is 1–6 hex
+/// digits, or exactly 4 hex digits without any braces.
+fn parse_unicode_escape(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+	location: Location,
+) -> Result<char, ParseError> {
+	let invalid = ParseError::InvalidUnicodeEscape { location };
+
+	let braced = parser.peek() == Some(&'{');
+	if braced {
+		parser.next();
+	}
+
+	let digits = parser
+		.peek_while(|c| c.is_ascii_hexdigit())
+		.take(if braced { 6 } else { 4 })
+		.collect::<String>();
+
+	let valid = match braced {
+		true => !digits.is_empty() && parser.next() == Some('}'),
+		false => digits.len() == 4,
+	};
+	if !valid {
+		return Err(invalid);
+	}
+
+	u32::from_str_radix(&digits, 16)
+		.ok()
+		.and_then(char::from_u32)
+		.ok_or(invalid)
+}
+
+#[cfg(test)]
+mod parse_escape_tests {
+	use super::*;
+
+	#[test]
+	fn every_form() {
+		for (escape, c) in [
+			("\\n", '\n'),
+			("\\0", '\0'),
+			("\\x41", 'A'),
+			("\\x7f", '\u{7F}'),
+			("\\u00e9", 'é'),
+			("\\u{e9}", 'é'),
+			("\\u{1F600}", '😀'),
+		] {
+			assert_eq!(
+				parse_string(&mut format!("\"{}\"", escape).as_str().into()).unwrap(),
+				Phrase::Text(c.to_string()),
+				"{}",
+				escape
+			);
+			assert_eq!(
+				parse_char(&mut format!("'{}'", escape).as_str().into()).unwrap(),
+				Phrase::Char(c),
+				"{}",
+				escape
+			);
+		}
+	}
+
+	#[test]
+	fn four_digits() {
+		// Without braces, only the first four digits belong to the escape
+		assert_eq!(
+			parse_string(&mut "\"\\u00e9e\"".into()).unwrap(),
+			Phrase::Text("ée".to_string())
+		);
+	}
+
+	#[test]
+	fn invalid_hex() {
+		for escape in ["\\x", "\\x4", "\\xg0", "\\x80", "\\xff"] {
+			for source in [format!("\"{}\"", escape), format!("'{}'", escape)] {
+				assert!(
+					matches!(
+						parse_phrase(&mut source.as_str().into()),
+						Err(ParseError::InvalidHexEscape { .. })
+					),
+					"{}",
+					source
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn invalid_unicode() {
+		for escape in ["\\u", "\\u12", "\\u{}", "\\u{d800}", "\\ud800"] {
+			for source in [format!("\"{}\"", escape), format!("'{}'", escape)] {
+				assert!(
+					matches!(
+						parse_phrase(&mut source.as_str().into()),
+						Err(ParseError::InvalidUnicodeEscape { .. })
+					),
+					"{}",
+					source
+				);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod parse_string_tests {
+	use super::*;
+
+	#[test]
+	fn invalid() {
+		let invalid = "invalid";
+		assert!(parse_string(&mut invalid.into()).is_err());
+	}
+
+	#[test]
+	fn valid() {
+		let hello = "\"hello!\"";
+		assert_eq!(
+			parse_string(&mut hello.into()).unwrap(),
+			Phrase::Text("hello!".to_string())
+		);
+	}
+
+	#[test]
+	fn escape_quote() {
+		let hello = r#""hello \"buddy\"!""#;
+		assert_eq!(
+			parse_string(&mut hello.into()).unwrap(),
+			Phrase::Text("hello \"buddy\"!".to_string())
+		);
+	}
+
+	#[test]
+	fn escapes() {
+		let escapes = r#""\n \t \r \\ \0""#;
+		assert_eq!(
+			parse_string(&mut escapes.into()).unwrap(),
+			Phrase::Text("\n \t \r \\ \0".to_string())
+		);
+	}
+
+	#[test]
+	fn multiple_lines() {
+		let source = "\"first\n\tsecond\r\nthird\"";
+		assert_eq!(
+			parse_string(&mut source.into()).unwrap(),
+			Phrase::Text("first\n\tsecond\r\nthird".to_string())
+		);
+	}
+
+	#[test]
+	fn line_continuation() {
+		for source in [
+			"\"one \\\n    two\"",
+			"\"one \\\r\n\t\ttwo\"",
+			"\"one \\\ntwo\"",
+		] {
+			assert_eq!(
+				parse_string(&mut source.into()).unwrap(),
+				Phrase::Text("one two".to_string()),
+				"{:?}",
+				source
+			);
+		}
+		// Only the indentation of the very next line is skipped
+		assert_eq!(
+			parse_string(&mut "\"a\\\n\n b\"".into()).unwrap(),
+			Phrase::Text("a\n b".to_string())
+		);
+	}
+
+	#[test]
+	fn unknown_escape() {
+		let unknown = r#""\q""#;
+		assert!(matches!(
+			parse_string(&mut unknown.into()),
+			Err(ParseError::UnknownEscape { found: 'q', .. })
+		));
+	}
+
+	#[test]
+	fn unicode_escape() {
+		let cafe = r#""caf\u{e9}""#;
+		assert_eq!(
+			parse_string(&mut cafe.into()).unwrap(),
+			Phrase::Text("café".to_string())
+		);
+	}
+
+	#[test]
+	fn astral_unicode_escape() {
+		let grin = r#""\u{1F600}""#;
+		assert_eq!(
+			parse_string(&mut grin.into()).unwrap(),
+			Phrase::Text("😀".to_string())
+		);
+	}
+
+	#[test]
+	fn surrogate_unicode_escape() {
+		let surrogate = r#""\u{D800}""#;
+		assert!(matches!(
+			parse_string(&mut surrogate.into()),
+			Err(ParseError::InvalidUnicodeEscape { .. })
+		));
+	}
+
+	#[test]
+	fn malformed_unicode_escape() {
+		for malformed in [r#""\u{}""#, r#""\u00e""#, r#""\u{1234567}""#, r#""\u{e9""#] {
+			assert!(matches!(
+				parse_string(&mut malformed.into()),
+				Err(ParseError::InvalidUnicodeEscape { .. })
+			));
+		}
+	}
+
+	#[test]
+	fn unterminated() {
+		let hello = "\"hello";
+		assert_eq!(
+			parse_string(&mut hello.into()),
+			Err(ParseError::UnterminatedString {
+				location: Location {
+					line: 0,
+					column: 0,
+					offset: 0
+				},
+				end: Location {
+					line: 0,
+					column: 6,
+					offset: 6
+				}
+			})
+		);
+	}
+
+	#[test]
+	fn stops() {
+		let hello = "\"hello!\"🏁";
+		let mut parser = hello.into();
+		assert_eq!(
+			parse_string(&mut parser).unwrap(),
+			Phrase::Text("hello!".to_string())
+		);
+		assert_eq!(parser.next(), Some('🏁'));
+	}
+
+	#[test]
+	fn carriage_return() {
+		let hello = "; hello!\r\n";
+		assert_eq!(
+			parse_comment(&mut hello.into()).unwrap(),
+			Phrase::Comment(" hello!".to_string())
+		);
+	}
+}
+
+pub fn parse_char(parser: &mut Parser<impl Iterator<Item = char>>) -> Result<Phrase, ParseError> {
+	scan_char(parser).map(Phrase::Char)
+}
+
+pub(super) fn scan_char(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<char, ParseError> {
+	let location = parser.location();
+
+	// Consume '
+	parser.expect('\'', "a character")?;
+
+	let c = match parser.next() {
+		Some('\'') => return Err(ParseError::EmptyCharacter { location }),
+		Some('\\') => parse_escape(parser)?,
+		Some(c) => c,
+		None => return Err(ParseError::UnterminatedCharacter { location }),
+	};
+
+	match parser.next() {
+		Some('\'') => Ok(c),
+		Some(c) if !c.is_whitespace() && !matches!(c, ')' | ']' | '}') => {
+			Err(ParseError::MultipleCharacters { location })
+		}
+		_ => Err(ParseError::UnterminatedCharacter { location }),
+	}
+}
+
+#[cfg(test)]
+mod parse_char_tests {
+	use super::*;
+
+	#[test]
+	fn valid() {
+		let a = "'a'";
+		assert_eq!(parse_char(&mut a.into()).unwrap(), Phrase::Char('a'));
+	}
+
+	#[test]
+	fn escaped() {
+		let new_line = r"'\n'";
+		assert_eq!(
+			parse_char(&mut new_line.into()).unwrap(),
+			Phrase::Char('\n')
+		);
+
+		let quote = r"'\''";
+		assert_eq!(parse_char(&mut quote.into()).unwrap(), Phrase::Char('\''));
+
+		let e = r"'\u{e9}'";
+		assert_eq!(parse_char(&mut e.into()).unwrap(), Phrase::Char('é'));
+	}
+
+	#[test]
+	fn empty() {
+		let empty = "''";
+		assert!(matches!(
+			parse_char(&mut empty.into()),
+			Err(ParseError::EmptyCharacter { .. })
+		));
+	}
+
+	#[test]
+	fn multiple_characters() {
+		let ab = "'ab'";
+		assert!(matches!(
+			parse_char(&mut ab.into()),
+			Err(ParseError::MultipleCharacters { .. })
+		));
+	}
+
+	#[test]
+	fn unterminated() {
+		for unterminated in ["'a", "'", "'a )"] {
+			assert!(matches!(
+				parse_char(&mut unterminated.into()),
+				Err(ParseError::UnterminatedCharacter { .. })
+			));
+		}
+	}
+}
+
+/// Parses a line comment, which begins with `;`, or whatever the parser was given with
+/// [`Parser::with_comment_prefix`], and runs to the end of the line.
+pub fn parse_comment(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<Phrase, ParseError> {
+	if !parser.at_comment() {
+		let location = parser.location();
+		return Err(ParseError::unexpected(parser.next(), "a comment", location));
+	}
+	parser.skip_comment_prefix();
+
+	let body = parser.take_while(|&c| c != '\n' && c != '\r').collect();
+	Ok(Phrase::Comment(body))
+}
+
+#[cfg(test)]
+mod parse_comment_tests {
+	use super::*;
+
+	#[test]
+	fn no_new_line() {
+		let hello = "; hello!";
+		assert_eq!(
+			parse_comment(&mut hello.into()).unwrap(),
+			Phrase::Comment(" hello!".to_string())
+		);
+	}
+
+	#[test]
+	fn stops() {
+		let hello = "; hello!\n🏁";
+		let mut parser = hello.into();
+		assert_eq!(
+			parse_comment(&mut parser).unwrap(),
+			Phrase::Comment(" hello!".to_string())
+		);
+		assert_eq!(parser.next(), Some('🏁'));
+	}
+}
+
+/// Parses a `#| ... |#` comment. Block comments nest, so that commenting out a region
+/// which already contains one works as expected.
+pub fn parse_block_comment(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<Phrase, ParseError> {
+	let location = parser.location();
+
+	// Consume #|
+	parser.expect('#', "a block comment")?;
+	parser.expect('|', "a block comment")?;
+
+	let mut depth = 1;
+	let mut body = String::new();
+	loop {
+		match parser.next() {
+			Some('|') if parser.peek() == Some(&'#') => {
+				parser.next();
+				depth -= 1;
+				if depth == 0 {
+					break;
+				}
+				body.push_str("|#");
+			}
+			Some('#') if parser.peek() == Some(&'|') => {
+				parser.next();
+				depth += 1;
+				body.push_str("#|");
+			}
+			Some(c) => body.push(c),
+			None => return Err(ParseError::UnterminatedComment { location }),
+		}
+	}
+
+	Ok(Phrase::Comment(body))
+}
+
+#[cfg(test)]
+mod parse_block_comment_tests {
+	use super::*;
+
+	#[test]
+	fn simple() {
+		let hello = "#| hello!\nfriend |#";
+		assert_eq!(
+			parse_block_comment(&mut hello.into()).unwrap(),
+			Phrase::Comment(" hello!\nfriend ".to_string())
+		);
+	}
+
+	#[test]
+	fn nested() {
+		let nested = "#| a #| b |# c |#🏁";
+		let mut parser = nested.into();
+		assert_eq!(
+			parse_block_comment(&mut parser).unwrap(),
+			Phrase::Comment(" a #| b |# c ".to_string())
+		);
+		assert_eq!(parser.next(), Some('🏁'));
+	}
+
+	#[test]
+	fn unterminated() {
+		let unterminated = "#| a #| b |# c";
+		assert_eq!(
+			parse_block_comment(&mut unterminated.into()),
+			Err(ParseError::UnterminatedComment {
+				location: Location {
+					line: 0,
+					column: 0,
+					offset: 0
+				}
+			})
+		);
+	}
+}
+
+pub fn parse_number(parser: &mut Parser<impl Iterator<Item = char>>) -> Result<Phrase, ParseError> {
+	let location = parser.location();
+	let literal = scan_number(parser, String::new())?;
+	Number::from_literal(&literal, location).map(Phrase::Number)
+}
+
+/// Scans a number onto the end of `number`, which holds anything already consumed, such
+/// as a sign.
+pub(super) fn scan_number(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+	mut number: String,
+) -> Result<String, ParseError> {
+	let location = parser.location();
+
+	if parser.peek() == Some(&'0') {
+		number.extend(parser.next());
+
+		let radix = match parser.peek() {
+			Some('x') => Some(16),
+			Some('o') => Some(8),
+			Some('b') => Some(2),
+			_ => None,
+		};
+
+		if let Some(radix) = radix {
+			number.extend(parser.next());
+			let digits = match radix {
+				16 => parse_hex_digits(parser, location)?,
+				_ => parse_radix_digits(parser, radix, location)?,
+			};
+			number.push_str(&digits);
+			check_number_suffix(parser)?;
+			return Ok(number);
+		}
+	}
+
+	let number = parse_decimal(parser, number, location)?;
+	check_number_suffix(parser)?;
+	Ok(number)
+}
+
+/// Makes sure that a number isn't followed directly by the start of an identifier, like
+/// in `123abc` or `1-2`, reporting where the identifier starts if it is.
+fn check_number_suffix(parser: &mut Parser<impl Iterator<Item = char>>) -> Result<(), ParseError> {
+	let is_identifier_start = |c: &char| c.is_alphabetic() || OPERATOR_CHARACTERS.contains(c);
+	match parser.peek().copied().filter(is_identifier_start) {
+		Some(found) => Err(ParseError::InvalidNumberSuffix {
+			found,
+			location: parser.location(),
+		}),
+		None => Ok(()),
+	}
+}
+
+/// Scans the rest of a decimal number, which may have a fractional part and an exponent,
+/// like `1`, `.5`, `2.5`, or `1.5e-3`. `number` holds anything already consumed. Digits
+/// may be separated by underscores, like `1_000`, which are left out of the result.
+fn parse_decimal(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+	mut number: String,
+	location: Location,
+) -> Result<String, ParseError> {
+	#[derive(Clone, Copy)]
+	enum State {
+		Integer,
+		Fraction,
+		Exponent,
+		ExponentSign,
+		ExponentDigits,
+	}
+
+	let mut state = State::Integer;
+	let mut has_digits = number.ends_with(|c: char| c.is_ascii_digit());
+
+	while let Some(&c) = parser.peek() {
+		state = match (state, c) {
+			(State::Integer | State::Fraction, '0'..='9') => {
+				has_digits = true;
+				state
+			}
+			(State::Integer | State::Fraction | State::ExponentDigits, '_') => {
+				let separates_digits = number.ends_with(|c: char| c.is_ascii_digit())
+					&& parser.peek_nth(1).is_some_and(char::is_ascii_digit);
+				if !separates_digits {
+					return Err(ParseError::MalformedNumber { location });
+				}
+				parser.next();
+				continue;
+			}
+			(State::Integer, '.') => State::Fraction,
+			(State::Integer | State::Fraction, 'e' | 'E') if has_digits => State::Exponent,
+			(State::Exponent, '+' | '-') => State::ExponentSign,
+			(State::Exponent | State::ExponentSign | State::ExponentDigits, '0'..='9') => {
+				State::ExponentDigits
+			}
+			_ => break,
+		};
+		number.extend(parser.next());
+	}
+
+	// A number needs at least one digit before any exponent, an exponent needs digits of
+	// its own, and there can only be one decimal point (`1.2.3`).
+	let is_complete = has_digits && !matches!(state, State::Exponent | State::ExponentSign);
+	if !is_complete || parser.peek() == Some(&'.') {
+		return Err(ParseError::MalformedNumber { location });
+	}
+
+	Ok(number)
+}
+
+/// Parses the digits following a `0x`, `0o`, or `0b` prefix, leaving out any underscores
+/// separating them.
+fn parse_radix_digits(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+	radix: u32,
+	location: Location,
+) -> Result<String, ParseError> {
+	let digits = take_radix_digits(parser, radix);
+
+	// Catch a missing number (`0x`), digits that are invalid for the radix (`0b12`), and
+	// misplaced separators (`0xF_`)
+	if digits.is_empty() || ends_badly(parser) {
+		return Err(ParseError::MalformedNumber { location });
+	}
+
+	Ok(digits)
+}
+
+/// Parses the digits following a `0x` prefix, which might also make up a hex float, like
+/// `0x1.8p3`. A hex float always needs its `p` exponent, which is a power of two written
+/// in decimal, because otherwise a hex `e` would be ambiguous.
+fn parse_hex_digits(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+	location: Location,
+) -> Result<String, ParseError> {
+	let mut digits = take_radix_digits(parser, 16);
+	if !matches!(parser.peek(), Some('.' | 'p' | 'P')) {
+		if digits.is_empty() || ends_badly(parser) {
+			return Err(ParseError::MalformedNumber { location });
+		}
+		return Ok(digits);
+	}
+
+	if parser.peek() == Some(&'.') {
+		digits.extend(parser.next());
+		let fraction = take_radix_digits(parser, 16);
+		if digits == "." && fraction.is_empty() {
+			return Err(ParseError::MalformedNumber { location });
+		}
+		digits.push_str(&fraction);
+	}
+
+	if digits.is_empty() || !matches!(parser.next(), Some('p' | 'P')) {
+		return Err(ParseError::MalformedNumber { location });
+	}
+	digits.push('p');
+	if let Some(sign) = parser.peek().copied().filter(|&c| c == '+' || c == '-') {
+		parser.next();
+		digits.push(sign);
+	}
+	let exponent = take_radix_digits(parser, 10);
+	if exponent.is_empty() || ends_badly(parser) {
+		return Err(ParseError::MalformedNumber { location });
+	}
+	digits.push_str(&exponent);
+
+	Ok(digits)
+}
+
+/// Consumes digits of `radix`, leaving out any underscores separating them.
+fn take_radix_digits(parser: &mut Parser<impl Iterator<Item = char>>, radix: u32) -> String {
+	let mut digits = String::new();
+	while let Some(&c) = parser.peek() {
+		let separates_digits =
+			c == '_' && !digits.is_empty() && parser.peek_nth(1).is_some_and(|c| c.is_digit(radix));
+		if !separates_digits && !c.is_digit(radix) {
+			break;
+		}
+		digits.extend(parser.next().filter(|&c| c != '_'));
+	}
+	digits
+}
+
+/// Whether the digits of a number were followed by something that should have been part
+/// of it, but couldn't be.
+fn ends_badly(parser: &mut Parser<impl Iterator<Item = char>>) -> bool {
+	parser
+		.peek()
+		.is_some_and(|&c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
+
+#[cfg(test)]
+mod parse_number_tests {
+	use super::*;
+
+	fn number(source: &str) -> Number {
+		match parse_number(&mut source.into()).unwrap() {
+			Phrase::Number(number) => number,
+			phrase => panic!("expected a number, got {:?}", phrase),
+		}
+	}
+
+	#[test]
+	fn hex_float() {
+		assert_eq!(number("0x1p4"), Number::Float(16.0));
+		assert_eq!(number("0x1.8p1"), Number::Float(3.0));
+		assert_eq!(number("0x1_0.8P+1"), Number::Float(33.0));
+		assert_eq!(number("0xffp-4"), Number::Float(15.9375));
+	}
+
+	#[test]
+	fn malformed_hex_float() {
+		for malformed in [
+			"0x1.8", "0x1.", "0x.p1", "0xp1", "0x1p", "0x1p+", "0x1.8pz", "0x1.gp1", "0x1p1.5",
+		] {
+			assert!(
+				matches!(
+					parse_number(&mut malformed.into()),
+					Err(ParseError::MalformedNumber { .. })
+				),
+				"{}",
+				malformed
+			);
+		}
+	}
+
+	#[test]
+	fn integer() {
+		assert_eq!(number("0"), Number::Integer(0));
+		assert_eq!(number("10"), Number::Integer(10));
+		assert_eq!(
+			number("9007199254740993"),
+			Number::Integer(9007199254740993)
+		);
+	}
+
+	#[test]
+	fn out_of_range() {
+		assert!(matches!(
+			parse_number(&mut "9223372036854775808".into()),
+			Err(ParseError::NumberOutOfRange { .. })
+		));
+	}
+
+	#[test]
+	fn float() {
+		assert_eq!(number("0.5"), Number::Float(0.5));
+		assert_eq!(number("1.25"), Number::Float(1.25));
+		assert_eq!(number("1."), Number::Float(1.0));
+	}
+
+	#[test]
+	fn leading_point() {
+		assert_eq!(number(".5"), Number::Float(0.5));
+	}
+
+	#[test]
+	fn exponent() {
+		assert_eq!(number("1e10"), Number::Float(1e10));
+		assert_eq!(number("2.5E-3"), Number::Float(2.5e-3));
+		assert_eq!(number("1.5e+3"), Number::Float(1.5e3));
+		assert_eq!(number("0e0"), Number::Float(0.0));
+	}
+
+	#[test]
+	fn malformed_decimal() {
+		for malformed in ["1.2.3", "1e", "1e-", "."] {
+			assert!(matches!(
+				parse_number(&mut malformed.into()),
+				Err(ParseError::MalformedNumber { .. })
+			));
+		}
+	}
+
+	#[test]
+	fn separators() {
+		assert_eq!(number("1_000_000"), Number::Integer(1_000_000));
+		assert_eq!(number("3_141.592_653"), Number::Float(3_141.592_653));
+		assert_eq!(number("1e1_0"), Number::Float(1e10));
+		assert_eq!(number("0xFF_FF"), Number::Integer(0xFFFF));
+		assert_eq!(number("0b1010_1010"), Number::Integer(0b1010_1010));
+	}
+
+	#[test]
+	fn malformed_separators() {
+		for malformed in [
+			"_1", "1_", "1__2", "1_.5", "1._5", "1.5_", "1_e5", "0x_F", "0xF_", "0xF__F",
+		] {
+			assert!(
+				matches!(
+					parse_number(&mut malformed.into()),
+					Err(ParseError::MalformedNumber { .. })
+				),
+				"{}",
+				malformed
+			);
+		}
+	}
+
+	#[test]
+	fn suffix() {
+		for (source, offset) in [
+			("(123abc)", 4),
+			("(1.5x)", 4),
+			("(-2e5e)", 5),
+			("(a 1é)", 4),
+			("(1-2)", 2),
+			("(1/2)", 2),
+			("(a 2.5+)", 6),
+			("(0xF|)", 4),
+			("(1|a|)", 2),
+		] {
+			assert!(
+				matches!(
+					crate::parse(source),
+					Err(ParseError::InvalidNumberSuffix { location, .. }) if location.offset == offset
+				),
+				"{}",
+				source
+			);
+		}
+		let program = crate::parse("(123 abc)").unwrap();
+		assert_eq!(program[0].values.len(), 2);
+		assert_eq!(
+			program[0].values[0].node,
+			Phrase::Number(Number::Integer(123))
+		);
+		assert_eq!(
+			program[0].values[1].node,
+			Phrase::Identifier("abc".to_string())
+		);
+	}
+
+	#[test]
+	fn hexadecimal() {
+		assert_eq!(number("0xFF"), Number::Integer(255));
+	}
+
+	#[test]
+	fn octal() {
+		assert_eq!(number("0o17"), Number::Integer(15));
+	}
+
+	#[test]
+	fn binary() {
+		assert_eq!(number("0b1010"), Number::Integer(10));
+	}
+
+	#[test]
+	fn malformed() {
+		for malformed in ["0xG", "0x", "0b12", "0o8"] {
+			assert!(matches!(
+				parse_number(&mut malformed.into()),
+				Err(ParseError::MalformedNumber { .. })
+			));
+		}
+	}
+}
+
+/// Characters which may appear after the first character of a text identifier, allowing
+/// names like `string-length`, `empty?`, and `Friend.name`.
+pub(super) fn is_identifier_character(c: char) -> bool {
+	c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '?' | '!' | '*')
+}
+
+/// Whether the next character is the `.` of a dotted pair, rather than the start of a
+/// number like `.5`.
+pub(super) fn is_dot(parser: &mut Parser<impl Iterator<Item = char>>) -> bool {
+	parser.peek() == Some(&'.')
+		&& parser
+			.peek_nth(1)
+			.is_none_or(|&c| c.is_whitespace() || matches!(c, ')' | ']' | '}'))
+}
+
+pub fn parse_text_identifier(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<Phrase, ParseError> {
+	let location = parser.location();
+	finish_text_identifier(parser, String::new(), location)
+}
+
+/// Adds characters to an identifier which began at `location` for as long as they
+/// match `predicate`, stopping as soon as it grows too long.
+fn extend_identifier(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+	identifier: &mut String,
+	predicate: impl Fn(char) -> bool,
+	location: Location,
+) -> Result<(), ParseError> {
+	while let Some(c) = parser.peek().copied().filter(|&c| predicate(c)) {
+		parser.next();
+		identifier.push(c);
+		parser.check_identifier_len(identifier, location)?;
+	}
+	Ok(())
+}
+
+/// The value of the `#t`, `#f`, `#true`, or `#false` coming up next, if there is one,
+/// along with how many characters long it is.
+pub(super) fn peek_hash_boolean(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Option<(bool, usize)> {
+	if parser.peek() != Some(&'#') {
+		return None;
+	}
+	let mut name = String::new();
+	while let Some(&c) = parser
+		.peek_nth(name.chars().count() + 1)
+		.filter(|&&c| is_identifier_character(c))
+	{
+		// Nothing longer than `false` can match, so there's no need to keep looking
+		if name.len() > "false".len() {
+			return None;
+		}
+		name.push(c);
+	}
+	let boolean = match name.as_str() {
+		"t" | "true" => true,
+		"f" | "false" => false,
+		_ => return None,
+	};
+	Some((boolean, name.len() + 1))
+}
+
+/// Parses a boolean written with the `#t` and `#f` syntax from Scheme, or the longer
+/// `#true` and `#false`.
+pub(super) fn scan_hash_boolean(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<bool, ParseError> {
+	let location = parser.location();
+	let (boolean, len) = peek_hash_boolean(parser).ok_or_else(|| {
+		ParseError::unexpected(parser.peek().copied(), "a boolean, like #t or #f", location)
+	})?;
+	for _ in 0..len {
+		parser.next();
+	}
+	Ok(boolean)
+}
+
+#[cfg(test)]
+mod scan_hash_boolean_tests {
+	use super::*;
+	use crate::parse;
+	use crate::structural_hash;
+
+	#[test]
+	fn booleans() {
+		for (source, boolean) in [
+			("#t", true),
+			("#f", false),
+			("#true", true),
+			("#false", false),
+		] {
+			let mut parser = Parser::from(source);
+			assert_eq!(scan_hash_boolean(&mut parser), Ok(boolean));
+			assert_eq!(parser.next(), None);
+		}
+		assert_eq!(
+			structural_hash(&parse("(a #t #f . #true)").unwrap()),
+			structural_hash(&parse("(a true false . true)").unwrap())
+		);
 	}
 
-	Ok(())
-}
+	#[test]
+	fn not_booleans() {
+		for source in ["#", "#tr", "#falsey", "#t-1", "#|t|#"] {
+			assert_eq!(peek_hash_boolean(&mut source.into()), None, "{}", source);
+		}
+		assert!(parse("(a #tx)").is_err());
+	}
 
-pub fn parse_string(parser: &mut Parser<impl Iterator<Item = char>>) -> miette::Result<Phrase> {
-	// Consume "
-	let quote = parser.next();
-	if quote != Some('"') {
-		return Err(miette!("entered parse_string with no string to parse"));
+	#[test]
+	fn with_comments() {
+		// Only the spans differ, since `#t` is shorter than `true`
+		assert_eq!(
+			structural_hash(&parse("(a #| b #t |# #t #; #f #f)").unwrap()),
+			structural_hash(&parse("(a #| b #t |# true #; false false)").unwrap())
+		);
 	}
+}
 
-	let mut is_next_escaped = false;
+/// Whether the next character is the `|` starting a quoted identifier like `|a b|`,
+/// rather than the start of an operator like `|>`, or `|` on its own.
+pub(super) fn is_quoted_identifier(parser: &mut Parser<impl Iterator<Item = char>>) -> bool {
+	parser.peek() == Some(&'|')
+		&& parser.peek_nth(1).is_some_and(|&c| {
+			!c.is_whitespace() && !matches!(c, ')' | ']' | '}') && !OPERATOR_CHARACTERS.contains(&c)
+		})
+}
 
-	let text = parser
-		.take_while(|&c| {
-			if is_next_escaped {
-				is_next_escaped = false;
-				return true;
-			}
+/// Parses an identifier wrapped in `|`s, which can contain anything at all, like
+/// `|hello world|`. Escapes work just like in strings, along with `\|` for a `|`.
+pub fn parse_quoted_identifier(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<Phrase, ParseError> {
+	let identifier = scan_quoted_identifier(parser)?;
+	Ok(parser.identifier(identifier))
+}
 
-			if c == '\\' {
-				is_next_escaped = true;
-				return true;
-			}
+/// Scans a quoted identifier, giving back what it holds with any escapes replaced.
+pub(super) fn scan_quoted_identifier(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<String, ParseError> {
+	let location = parser.location();
 
-			c != '"'
-		})
-		.collect();
+	// Consume |
+	parser.expect('|', "a quoted identifier")?;
 
-	Ok(Phrase::Text(text))
+	let mut identifier = String::new();
+	loop {
+		match parser.next() {
+			Some('|') => break,
+			Some('\\') if parser.peek() == Some(&'|') => {
+				parser.next();
+				identifier.push('|');
+			}
+			Some('\\') => identifier.push(parse_escape(parser)?),
+			Some(c) => identifier.push(c),
+			None => return Err(ParseError::UnterminatedIdentifier { location }),
+		}
+		parser.check_identifier_len(&identifier, location)?;
+	}
+
+	Ok(identifier)
 }
 
 #[cfg(test)]
-mod parse_string_tests {
+mod parse_quoted_identifier_tests {
 	use super::*;
 
 	#[test]
-	fn invalid() {
-		let invalid = "invalid";
-		assert!(parse_string(&mut invalid.into()).is_err());
+	fn quoted() {
+		for (source, identifier) in [
+			("|a b|", "a b"),
+			("|a\\|b|", "a|b"),
+			("|a\\nb|", "a\nb"),
+			("|(nil)|", "(nil)"),
+			("|true|", "true"),
+		] {
+			assert_eq!(
+				parse_phrase(&mut source.into()).unwrap().node,
+				Phrase::Identifier(identifier.to_string())
+			);
+		}
 	}
 
 	#[test]
-	fn valid() {
-		let hello = "\"hello!\"";
+	fn operators() {
+		// Without anything inside, a `|` is still just an operator
+		for (source, operator) in [("|", "|"), ("||", "||"), ("|>", "|>"), ("|> a|", "|>")] {
+			assert_eq!(
+				parse_phrase(&mut source.into()).unwrap().node,
+				Phrase::Identifier(operator.to_string())
+			);
+		}
+	}
+
+	#[test]
+	fn unterminated() {
 		assert_eq!(
-			parse_string(&mut hello.into()).unwrap(),
-			Phrase::Text("hello!".to_string())
+			parse_phrase(&mut "|foo".into()),
+			Err(ParseError::UnterminatedIdentifier {
+				location: crate::parser::Location::default()
+			})
 		);
 	}
 
 	#[test]
-	fn escape_quote() {
-		let hello = r#""hello \"buddy\"!""#;
+	fn round_trip() {
+		for identifier in [
+			"a b",
+			"a|b",
+			"1st",
+			"true",
+			" a",
+			"|>x",
+			"a\\b",
+			"tab\there",
+		] {
+			let phrase = Phrase::Identifier(identifier.to_string());
+			let printed = phrase.to_string();
+			assert_eq!(
+				parse_phrase(&mut printed.as_str().into()).unwrap().node,
+				phrase,
+				"{}",
+				printed
+			);
+		}
+	}
+}
+
+/// Scans the rest of a text identifier onto the end of `identifier`, which holds
+/// anything already consumed.
+fn finish_text_identifier(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+	mut identifier: String,
+	location: Location,
+) -> Result<Phrase, ParseError> {
+	extend_identifier(parser, &mut identifier, is_identifier_character, location)?;
+
+	Ok(match identifier.as_str() {
+		"true" => Phrase::Boolean(true),
+		"false" => Phrase::Boolean(false),
+		"nil" => Phrase::Nil,
+		_ => parser.identifier(identifier),
+	})
+}
+
+#[cfg(test)]
+mod parse_text_identifier_tests {
+	use super::*;
+
+	#[test]
+	fn identifier() {
+		for identifier in [
+			"hello",
+			"describe-friend",
+			"Friend.name",
+			"my-cool-fn?",
+			"set!",
+			"mul*",
+			"snake_case",
+			"trueish",
+			"nilable",
+		] {
+			assert_eq!(
+				parse_text_identifier(&mut identifier.into()).unwrap(),
+				Phrase::Identifier(identifier.to_string())
+			);
+		}
+	}
+
+	#[test]
+	fn unicode() {
+		for identifier in ["λ", "café", "привет-world", "日本語"] {
+			assert_eq!(
+				parse_text_identifier(&mut identifier.into()).unwrap(),
+				Phrase::Identifier(identifier.to_string())
+			);
+		}
+	}
+
+	#[test]
+	fn location() {
+		let mut parser = "foo bar".into();
+		parse_text_identifier(&mut parser).unwrap();
+		// The space that ended the identifier was only peeked at, not consumed
 		assert_eq!(
-			parse_string(&mut hello.into()).unwrap(),
-			Phrase::Text("hello \\\"buddy\\\"!".to_string())
+			parser.location(),
+			crate::parser::Location {
+				line: 0,
+				column: 3,
+				offset: 3
+			}
 		);
+		assert_eq!(parser.peek(), Some(&' '));
 	}
 
 	#[test]
 	fn stops() {
-		let hello = "\"hello!\"🏁";
-		let mut parser = hello.into();
+		let friend = "friend)";
+		let mut parser = friend.into();
 		assert_eq!(
-			parse_string(&mut parser).unwrap(),
-			Phrase::Text("hello!".to_string())
+			parse_text_identifier(&mut parser).unwrap(),
+			Phrase::Identifier("friend".to_string())
+		);
+		assert_eq!(parser.next(), Some(')'));
+	}
+
+	#[test]
+	fn keywords() {
+		assert_eq!(
+			parse_text_identifier(&mut "true".into()).unwrap(),
+			Phrase::Boolean(true)
+		);
+		assert_eq!(
+			parse_text_identifier(&mut "false".into()).unwrap(),
+			Phrase::Boolean(false)
+		);
+		assert_eq!(
+			parse_text_identifier(&mut "nil".into()).unwrap(),
+			Phrase::Nil
 		);
-		assert_eq!(parser.next(), Some('🏁'));
 	}
 }
 
-pub fn parse_comment(parser: &mut Parser<impl Iterator<Item = char>>) -> miette::Result<Phrase> {
-	// Consume ;
-	let start = parser.next();
-	if start != Some(';') {
-		return Err(miette!("entered parse_comment with no comment to parse"));
+pub(super) static OPERATOR_CHARACTERS: [char; 13] = [
+	'*', '+', '-', '/', '<', '>', '=', '!', '$', '|', '?', '^', '~',
+];
+pub fn parse_operator_identifier(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<Phrase, ParseError> {
+	let location = parser.location();
+	let mut identifier = String::new();
+	extend_identifier(
+		parser,
+		&mut identifier,
+		|c| OPERATOR_CHARACTERS.contains(&c),
+		location,
+	)?;
+
+	// A lone sign directly followed by a digit is actually a signed number, like `-5`
+	let is_sign = identifier == "-" || identifier == "+";
+	if is_sign && parser.peek().is_some_and(|c| c.is_ascii_digit()) {
+		let literal = scan_number(parser, identifier)?;
+		return Number::from_literal(&literal, location).map(Phrase::Number);
 	}
 
-	let body = parser.take_while(|&c| c != '\n').collect();
-	Ok(Phrase::Comment(body))
+	Ok(parser.identifier(identifier))
 }
 
 #[cfg(test)]
-mod parse_comment_tests {
+mod parse_operator_identifier_tests {
 	use super::*;
 
 	#[test]
-	fn no_new_line() {
-		let hello = "; hello!";
+	fn operators() {
+		for operator in ["-", "+", "->", "|>", "<+>"] {
+			assert_eq!(
+				parse_operator_identifier(&mut operator.into()).unwrap(),
+				Phrase::Identifier(operator.to_string())
+			);
+		}
+	}
+
+	#[test]
+	fn signed_numbers() {
+		for (source, number) in [
+			("-5", Number::Integer(-5)),
+			("-2.5", Number::Float(-2.5)),
+			("+2", Number::Integer(2)),
+			("-0x10", Number::Integer(-16)),
+		] {
+			assert_eq!(
+				parse_operator_identifier(&mut source.into()).unwrap(),
+				Phrase::Number(number)
+			);
+		}
+	}
+
+	#[test]
+	fn bare_sign() {
+		for source in ["- 5", "-)", "-(x)"] {
+			let mut parser = source.into();
+			assert_eq!(
+				parse_operator_identifier(&mut parser).unwrap(),
+				Phrase::Identifier("-".to_string())
+			);
+			assert_eq!(parser.location().column, 1);
+		}
+	}
+}
+
+/// Parses a `::type` annotation, returning the name of the type.
+pub fn parse_type(parser: &mut Parser<impl Iterator<Item = char>>) -> Result<String, ParseError> {
+	parser.expect(':', "a type")?;
+	parser.expect(':', "a type")?;
+
+	// This function unfortunately can't quite just use `parse_phrase`, because that
+	// would allow weird things like `a::b::c` since `parse_phrase` calls us. We need some
+	// mechanism to prevent annotating types with types. I guess that could be done as a
+	// separate validation step, but I feel like it shouldn't make it past the parser.
+	let name = parser.peek_while(|&c| is_identifier_character(c)).collect();
+
+	Ok(name)
+}
+
+/// Parses a `:keyword`, whose name follows the same rules as an identifier.
+pub fn parse_keyword(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<Phrase, ParseError> {
+	parser.expect(':', "a keyword")?;
+
+	let location = parser.location();
+	let mut keyword = String::new();
+	extend_identifier(parser, &mut keyword, is_identifier_character, location)?;
+	if keyword.is_empty() {
+		return Err(ParseError::unexpected(
+			parser.peek().copied(),
+			"the name of a keyword",
+			location,
+		));
+	}
+
+	Ok(Phrase::Keyword(keyword))
+}
+
+#[cfg(test)]
+mod parse_keyword_tests {
+	use super::*;
+
+	#[test]
+	fn keyword() {
+		for (source, keyword) in [(":name", "name"), (":my-key?", "my-key?"), (":a1", "a1")] {
+			assert_eq!(
+				parse_keyword(&mut source.into()).unwrap(),
+				Phrase::Keyword(keyword.to_string())
+			);
+		}
+	}
+
+	#[test]
+	fn in_item() {
+		let item = "(:a :b)";
+		let mut expression = crate::parser::parse_expression(&mut item.into()).unwrap();
 		assert_eq!(
-			parse_comment(&mut hello.into()).unwrap(),
-			Phrase::Comment(" hello!".to_string())
+			std::mem::take(&mut expression.values)
+				.into_iter()
+				.map(|value| value.node)
+				.collect::<Vec<_>>(),
+			vec![
+				Phrase::Keyword("a".to_string()),
+				Phrase::Keyword("b".to_string())
+			]
 		);
 	}
 
 	#[test]
-	fn stops() {
-		let hello = "; hello!\n🏁";
-		let mut parser = hello.into();
+	fn no_name() {
 		assert_eq!(
-			parse_comment(&mut parser).unwrap(),
-			Phrase::Comment(" hello!".to_string())
+			parse_keyword(&mut ":".into()),
+			Err(ParseError::UnexpectedEof {
+				expected: "the name of a keyword",
+				location: Location {
+					line: 0,
+					column: 1,
+					offset: 1
+				},
+			})
 		);
-		assert_eq!(parser.next(), Some('🏁'));
+		assert!(matches!(
+			parse_keyword(&mut ": a".into()),
+			Err(ParseError::UnexpectedCharacter { found: ' ', .. })
+		));
 	}
 }
 
-pub fn parse_number(parser: &mut Parser<impl Iterator<Item = char>>) -> miette::Result<Phrase> {
-	let mut contains_point = false;
+/// The reader shorthands for quoting, like `'x` for `(quote x)`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum QuoteKind {
+	Quote,
+	Quasiquote,
+	Unquote,
+	UnquoteSplicing,
+}
 
-	let number = parser
-		.peek_while(|&c: &char| {
-			if !contains_point && c == '.' {
-				contains_point = true;
-				return true;
-			}
+impl QuoteKind {
+	/// The name of what the shorthand stands for.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Self::Quote => "quote",
+			Self::Quasiquote => "quasiquote",
+			Self::Unquote => "unquote",
+			Self::UnquoteSplicing => "unquote-splicing",
+		}
+	}
+}
 
-			c.is_ascii_digit()
-		})
-		.collect();
+/// Whether the upcoming `'` begins a character literal like `'a'`, rather than quoting
+/// whatever follows it like `'a`. It does if another `'` shows up before the phrase
+/// after it would end, even if there's too much in between to be a valid character.
+fn is_char_literal(parser: &mut Parser<impl Iterator<Item = char>>) -> bool {
+	if parser.peek_nth(1) == Some(&'\\') || parser.peek_nth(2) == Some(&'\'') {
+		return true;
+	}
+	// An empty character literal isn't valid anyway, so `''x` quotes a quote
+	if parser.peek_nth(1) == Some(&'\'') {
+		return false;
+	}
 
-	Ok(Phrase::Number(number))
+	let mut n = 1;
+	loop {
+		match parser.peek_nth(n) {
+			Some('\'') => return true,
+			Some(&c) if c.is_whitespace() || matches!(c, '(' | ')' | '[' | ']' | '{' | '}') => {
+				return false;
+			}
+			Some(_) => n += 1,
+			None => return false,
+		}
+	}
 }
 
-pub fn parse_text_identifier(
+/// Consumes a quoting shorthand, if one comes next.
+pub(super) fn parse_quote_prefix(
 	parser: &mut Parser<impl Iterator<Item = char>>,
-) -> miette::Result<Phrase> {
-	let identifier = parser
-		.peek_while(|&c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
-		.collect();
+) -> Option<QuoteKind> {
+	let next = *parser.peek()?;
+	let quote = match next {
+		'\'' if !is_char_literal(parser) => QuoteKind::Quote,
+		'`' => QuoteKind::Quasiquote,
+		',' => QuoteKind::Unquote,
+		_ => return None,
+	};
+	parser.next();
 
-	Ok(Phrase::Identifier(identifier))
+	if quote == QuoteKind::Unquote && parser.peek() == Some(&'@') {
+		parser.next();
+		return Some(QuoteKind::UnquoteSplicing);
+	}
+	Some(quote)
 }
 
-static OPERATOR_CHARACTERS: [char; 13] = [
-	'*', '+', '-', '/', '<', '>', '=', '!', '$', '|', '?', '^', '~',
-];
-pub fn parse_operator_identifier(
+pub fn parse_phrase(
 	parser: &mut Parser<impl Iterator<Item = char>>,
-) -> miette::Result<Phrase> {
-	let identifier = parser
-		.peek_while(|c| OPERATOR_CHARACTERS.contains(c))
-		.collect();
+) -> Result<Spanned<Phrase>, ParseError> {
+	skip_datum_comments(parser)?;
 
-	Ok(Phrase::Identifier(identifier))
-}
+	let location = parser.location();
+	let phrase =
+		if let Some(reader_macro) = parser.peek().copied().and_then(|c| parser.reader_macro(c)) {
+			parser.add_nodes(1, location)?;
+			let phrase = reader_macro(parser)?;
+			Spanned::new(phrase, Span::new(location, parser.location()))
+		} else {
+			if let Some(quote) = parse_quote_prefix(parser) {
+				return parse_quoted(parser, quote, location);
+			}
 
-pub fn parse_type(parser: &mut Parser<impl Iterator<Item = char>>) -> miette::Result<()> {
-	let prefix = parser.take(2).collect::<String>();
-	if prefix != "::" {
-		return Err(miette!("entered parse_type with no type to parse"));
-	}
+			match parser.peek() {
+				Some('(' | '[' | '{') => {
+					let expression = parse_delimited_expression(parser)?;
+					Spanned::new(
+						Phrase::Expression(expression),
+						Span::new(location, parser.location()),
+					)
+				}
+				_ => parse_atom(parser)?,
+			}
+		};
 
-	// This function unfortunately can't quite just use `parse_phrase`, because that
-	// would allow weird things like `a::b::c` since `parse_phrase` calls us. We need some
-	// mechanism to prevent annotating types with types. I guess that could be done as a
-	// separate validation step, but I feel like it shouldn't make it past the parser.
-	parse_text_identifier(parser)?;
+	if parser.peek() == Some(&':') {
+		parse_type(parser)?;
+	};
 
-	Ok(())
+	Ok(phrase)
 }
 
-pub fn parse_label(parser: &mut Parser<impl Iterator<Item = char>>) -> miette::Result<Phrase> {
-	let prefix = parser.next().ok_or(miette!("nothing left to parse"))?;
+#[cfg(test)]
+mod reader_macro_tests {
+	use super::*;
+	use crate::parser::parse_program;
+	use std::str::Chars;
+
+	/// Expands `@x` into `(splice x)`, whatever character it's registered for.
+	fn parse_splice(parser: &mut Parser<Chars>) -> Result<Phrase, ParseError> {
+		let location = parser.location();
+		parser.next();
+		let name = Spanned::new(
+			Phrase::Identifier("splice".to_string()),
+			Span::new(location, parser.location()),
+		);
+		let spliced = parse_phrase(parser)?;
+		Ok(Phrase::Expression(Expression {
+			kind: ExpressionKind::Item,
+			span: Span::new(location, spliced.span.end),
+			values: vec![name, spliced],
+			leading_comments: vec![],
+			tail: None,
+		}))
+	}
+
+	#[test]
+	fn splice() {
+		let mut parser = Parser::from("(a @foo @(b c))").with_reader_macro('@', parse_splice);
+		let program = parse_program(&mut parser).unwrap();
+		assert_eq!(program[0].to_string(), "(a (splice foo) (splice (b c)))",);
+		assert_eq!(program[0].values[1].span.bytes(), 3..7);
+	}
+
+	#[test]
+	fn overrides_built_in_syntax() {
+		let mut parser = Parser::from("(a 'b)").with_reader_macro('\'', parse_splice);
+		let program = parse_program(&mut parser).unwrap();
+		assert_eq!(program[0].to_string(), "(a (splice b))");
+	}
 
-	if prefix != ':' {
-		return Err(miette!("entered parse_label on a non-label"));
+	#[test]
+	fn built_in_parsers() {
+		let source = "(a \"b\")";
+		let mut parser = Parser::from(source).with_reader_macro('"', parse_string);
+		assert_eq!(
+			parse_program(&mut parser),
+			parse_program(&mut source.into())
+		);
 	}
+}
 
-	let label: String = parser.peek_while(|c| c.is_alphanumeric()).collect();
+/// Parses whatever follows a quoting shorthand which began at `location`, expanding it
+/// into an item like `(quote x)`.
+fn parse_quoted(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+	quote: QuoteKind,
+	location: Location,
+) -> Result<Spanned<Phrase>, ParseError> {
+	let name = Spanned::new(
+		parser.identifier(quote.name().to_string()),
+		Span::new(location, parser.location()),
+	);
+	// The expansion is an item holding the name of the quote, along with what's quoted
+	parser.add_nodes(2, location)?;
+	// Quotes of quotes of quotes recurse just like nested expressions do
+	parser.enter(location)?;
+	let quoted = parse_phrase(parser)?;
+	parser.exit();
 
-	Ok(Phrase::Label(label))
+	let span = Span::new(location, quoted.span.end);
+	let expression = Expression {
+		kind: ExpressionKind::Item,
+		values: vec![name, quoted],
+		leading_comments: vec![],
+		tail: None,
+		span,
+	};
+	Ok(Spanned::new(Phrase::Expression(expression), span))
 }
 
-pub fn parse_phrase(parser: &mut Parser<impl Iterator<Item = char>>) -> miette::Result<Phrase> {
+/// Everything that can begin a phrase, for errors about finding something else instead.
+pub(super) const EXPECTED_PHRASE: &str =
+	"a phrase, like an expression, string, character, number, identifier, keyword, or comment";
+
+/// Parses any phrase which isn't an expression, without any type annotation that
+/// might follow it.
+pub fn parse_atom(
+	parser: &mut Parser<impl Iterator<Item = char>>,
+) -> Result<Spanned<Phrase>, ParseError> {
 	parse_whitespace(parser)?;
 
-	let phrase = match parser.peek().ok_or(miette!("unexpected end of file"))? {
-		'(' | '[' | '{' => parse_expression(parser).map(Phrase::Expression),
+	let location = parser.location();
+	let next = *parser.peek().ok_or(ParseError::UnexpectedEof {
+		expected: EXPECTED_PHRASE,
+		location,
+	})?;
+	parser.add_nodes(1, location)?;
+	let node = match next {
+		_ if parser.at_comment() => parse_comment(parser),
 		'"' => parse_string(parser),
-		';' => parse_comment(parser),
-		':' => parse_label(parser),
+		'\'' => parse_char(parser),
+		'#' if peek_hash_boolean(parser).is_some() => {
+			scan_hash_boolean(parser).map(Phrase::Boolean)
+		}
+		'#' => parse_block_comment(parser),
+		':' => parse_keyword(parser),
+		'.' if is_dot(parser) => Err(ParseError::MalformedPair { location }),
+		'.' => parse_number(parser),
 		x if x.is_ascii_digit() => parse_number(parser),
-		x if x.is_ascii_alphabetic() => parse_text_identifier(parser),
-		x if OPERATOR_CHARACTERS.contains(x) => parse_operator_identifier(parser),
-		_ => Err(miette!("unexpected character")),
-	};
+		'r' => parse_raw_string(parser),
+		x if x.is_alphabetic() => parse_text_identifier(parser),
+		'|' if is_quoted_identifier(parser) => parse_quoted_identifier(parser),
+		x if OPERATOR_CHARACTERS.contains(&x) => parse_operator_identifier(parser),
+		x => Err(ParseError::unexpected(Some(x), EXPECTED_PHRASE, location)),
+	}?;
 
-	if parser.peek() == Some(&':') {
-		parse_type(parser)?;
-	};
+	Ok(Spanned::new(node, Span::new(location, parser.location())))
+}
+
+#[cfg(test)]
+mod parse_phrase_tests {
+	use super::*;
+	use crate::parser::Location;
 
-	phrase
+	#[test]
+	fn expected() {
+		let error = parse_phrase(&mut ")".into()).unwrap_err();
+		assert_eq!(
+			error,
+			ParseError::UnexpectedCharacter {
+				found: ')',
+				expected: EXPECTED_PHRASE,
+				location: Location::default(),
+			}
+		);
+		assert_eq!(
+			error.to_string(),
+			"unexpected character ), expected a phrase, like an expression, string, character, number, identifier, keyword, or comment"
+		);
+	}
+
+	#[test]
+	fn span_start() {
+		let foo = "  foo";
+		let phrase = parse_phrase(&mut foo.into()).unwrap();
+		assert_eq!(phrase.node, Phrase::Identifier("foo".to_string()));
+		assert_eq!(
+			phrase.span.start,
+			Location {
+				line: 0,
+				column: 2,
+				offset: 2
+			}
+		);
+	}
+
+	#[test]
+	fn control_characters() {
+		let error = parse_phrase(&mut "\0".into()).unwrap_err();
+		assert_eq!(
+			error,
+			ParseError::IllegalCharacter {
+				found: '\0',
+				location: Location::default(),
+			}
+		);
+		assert_eq!(
+			error.to_string(),
+			"illegal character U+0000, control characters are only allowed inside of strings and comments"
+		);
+
+		let program = crate::parser::parse_program(&mut "(a \u{7}) (\x1b)".into());
+		assert!(matches!(
+			program,
+			Err(ParseError::IllegalCharacter { found: '\u{7}', .. })
+		));
+
+		// Inside of literals they're kept as they are, and the whitespace ones are just
+		// whitespace anywhere
+		let program =
+			crate::parser::parse_program(&mut "(\"\0\u{7}\" '\0' ; \0\n\u{b}a\u{c})".into())
+				.unwrap();
+		assert_eq!(
+			program[0].values[0].node,
+			Phrase::Text("\0\u{7}".to_string())
+		);
+		assert_eq!(program[0].values[1].node, Phrase::Char('\0'));
+		assert_eq!(
+			program[0].values[3].node,
+			Phrase::Identifier("a".to_string())
+		);
+	}
+
+	#[test]
+	fn unicode_dispatch() {
+		let lambda = "λx";
+		assert_eq!(
+			parse_phrase(&mut lambda.into()).unwrap().node,
+			Phrase::Identifier("λx".to_string())
+		);
+
+		let one = "1";
+		assert_eq!(
+			parse_phrase(&mut one.into()).unwrap().node,
+			Phrase::Number(Number::Integer(1))
+		);
+	}
+
+	#[test]
+	fn span_of_string() {
+		let hello = "\"hello\" ";
+		let phrase = parse_phrase(&mut hello.into()).unwrap();
+		assert_eq!(
+			phrase.span,
+			Span::new(
+				Location {
+					line: 0,
+					column: 0,
+					offset: 0
+				},
+				Location {
+					line: 0,
+					column: 7,
+					offset: 7
+				}
+			)
+		);
+	}
+
+	fn quoted(quote: &str, phrase: Phrase) -> Phrase {
+		match phrase {
+			Phrase::Expression(expression) => {
+				assert_eq!(expression.kind, ExpressionKind::Item);
+				assert_eq!(expression.values.len(), 2);
+				assert_eq!(
+					expression.values[0].node,
+					Phrase::Identifier(quote.to_string())
+				);
+				expression.values[1].node.clone()
+			}
+			phrase => panic!("expected a quote, got {:?}", phrase),
+		}
+	}
+
+	#[test]
+	fn quotes() {
+		for (source, quote) in [
+			("'x", "quote"),
+			("`x", "quasiquote"),
+			(",x", "unquote"),
+			(",@x", "unquote-splicing"),
+		] {
+			let phrase = parse_phrase(&mut source.into()).unwrap();
+			assert_eq!(
+				quoted(quote, phrase.node),
+				Phrase::Identifier("x".to_string())
+			);
+			assert_eq!(
+				phrase.span.end,
+				Location {
+					line: 0,
+					column: source.len() as u32,
+					offset: source.len(),
+				}
+			);
+		}
+	}
+
+	#[test]
+	fn nested_quotes() {
+		let source = "''x";
+		let phrase = parse_phrase(&mut source.into()).unwrap().node;
+		assert_eq!(
+			quoted("quote", quoted("quote", phrase)),
+			Phrase::Identifier("x".to_string())
+		);
+
+		let source = "`(a ,b ,@c)";
+		let phrase = parse_phrase(&mut source.into()).unwrap().node;
+		let Phrase::Expression(expression) = quoted("quasiquote", phrase) else {
+			panic!("expected an expression");
+		};
+		assert_eq!(
+			quoted("unquote", expression.values[1].node.clone()),
+			Phrase::Identifier("b".to_string())
+		);
+		assert_eq!(
+			quoted("unquote-splicing", expression.values[2].node.clone()),
+			Phrase::Identifier("c".to_string())
+		);
+	}
+
+	#[test]
+	fn quotes_and_characters() {
+		for (source, c) in [("'a'", 'a'), ("'\\''", '\''), ("'('", '('), ("' '", ' ')] {
+			assert_eq!(
+				parse_phrase(&mut source.into()).unwrap().node,
+				Phrase::Char(c)
+			);
+		}
+		assert!(matches!(
+			parse_phrase(&mut "'ab'".into()),
+			Err(ParseError::MultipleCharacters { .. })
+		));
+		assert_eq!(
+			quoted("quote", parse_phrase(&mut "'ab".into()).unwrap().node),
+			Phrase::Identifier("ab".to_string())
+		);
+	}
 }
@@ -0,0 +1,108 @@
+//! A readable outline of a parsed tree, for debugging. Unlike the derived `Debug`, it
+//! leaves out spans, and labels each node as briefly as it can, like
+//! `List[ Ident("a") Number(1) ]`.
+
+use std::fmt::Write;
+
+use super::Expression;
+use super::Phrase;
+
+/// How much each level of nesting is indented by.
+const INDENT: &str = "  ";
+
+impl Expression {
+	/// Renders the expression as an indented outline. Expressions holding only atoms are
+	/// kept on one line, and anything else gets a line for each of its values.
+	pub fn pretty(&self) -> String {
+		let mut out = String::new();
+		write_expression(&mut out, self, 0);
+		out
+	}
+}
+
+fn write_expression(out: &mut String, expression: &Expression, depth: usize) {
+	for comment in &expression.leading_comments {
+		let _ = writeln!(out, "Comment({:?})", comment);
+		out.push_str(&INDENT.repeat(depth));
+	}
+
+	let label = format!("{:?}", expression.kind);
+	let values = expression.values.iter().chain(expression.tail.as_deref());
+	let is_flat = values
+		.clone()
+		.all(|value| !matches!(value.node, Phrase::Expression(_)));
+
+	if expression.values.is_empty() && expression.tail.is_none() {
+		let _ = write!(out, "{}[]", label);
+		return;
+	}
+
+	let _ = write!(out, "{}[", label);
+	for (i, value) in values.enumerate() {
+		match is_flat {
+			true => out.push(' '),
+			false => {
+				out.push('\n');
+				out.push_str(&INDENT.repeat(depth + 1));
+			}
+		}
+		if i == expression.values.len() {
+			out.push_str(". ");
+		}
+		write_phrase(out, &value.node, depth + 1);
+	}
+	match is_flat {
+		true => out.push(' '),
+		false => {
+			out.push('\n');
+			out.push_str(&INDENT.repeat(depth));
+		}
+	}
+	out.push(']');
+}
+
+fn write_phrase(out: &mut String, phrase: &Phrase, depth: usize) {
+	let _ = match phrase {
+		Phrase::Expression(expression) => {
+			write_expression(out, expression, depth);
+			Ok(())
+		}
+		Phrase::Identifier(name) => write!(out, "Ident({:?})", name),
+		Phrase::Symbol(symbol) => write!(out, "Symbol({} {:?})", symbol.id(), symbol.name()),
+		Phrase::Text(text) => write!(out, "Text({:?})", text),
+		Phrase::Char(c) => write!(out, "Char({:?})", c),
+		Phrase::Number(number) => write!(out, "Number({})", number),
+		Phrase::Comment(body) => write!(out, "Comment({:?})", body),
+		Phrase::Keyword(name) => write!(out, "Keyword({:?})", name),
+		Phrase::Boolean(boolean) => write!(out, "Boolean({})", boolean),
+		Phrase::Nil => write!(out, "Nil"),
+	};
+}
+
+#[cfg(test)]
+mod pretty_tests {
+	use super::*;
+
+	#[test]
+	fn flat() {
+		let expression: Expression = "[a 1 \"two\" 'c' :d true nil]".parse().unwrap();
+		assert_eq!(
+			expression.pretty(),
+			r#"List[ Ident("a") Number(1) Text("two") Char('c') Keyword("d") Boolean(true) Nil ]"#
+		);
+		let empty: Expression = "()".parse().unwrap();
+		assert_eq!(empty.pretty(), "Item[]");
+	}
+
+	#[test]
+	fn nested() {
+		let source = include_str!("../../tests/testdata/pretty/nested.yall");
+		let expected = include_str!("../../tests/testdata/pretty/nested.txt");
+		let pretty = crate::parse(source)
+			.unwrap()
+			.iter()
+			.map(|expression| expression.pretty() + "\n")
+			.collect::<String>();
+		assert_eq!(pretty, expected);
+	}
+}
@@ -0,0 +1,101 @@
+//! A whole parsed program, which is mostly just its expressions, along with whatever
+//! comments come after them.
+
+use std::fmt;
+use std::fmt::Display;
+use std::ops::Deref;
+use std::ops::DerefMut;
+
+use super::phrase::write_comment;
+use super::Expression;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Program {
+	pub expressions: Vec<Expression>,
+	/// The bodies of any comments after the last expression, in order. Any other comment
+	/// belongs to an expression, either inside of it or leading it, but these have no
+	/// expression after them to lead.
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+	pub trailing_comments: Vec<String>,
+}
+
+impl Program {
+	pub fn new(expressions: Vec<Expression>, trailing_comments: Vec<String>) -> Self {
+		Self {
+			expressions,
+			trailing_comments,
+		}
+	}
+}
+
+impl Deref for Program {
+	type Target = Vec<Expression>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.expressions
+	}
+}
+
+impl DerefMut for Program {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.expressions
+	}
+}
+
+impl From<Vec<Expression>> for Program {
+	fn from(expressions: Vec<Expression>) -> Self {
+		Self::new(expressions, vec![])
+	}
+}
+
+impl FromIterator<Expression> for Program {
+	fn from_iter<T: IntoIterator<Item = Expression>>(iter: T) -> Self {
+		Self::from(iter.into_iter().collect::<Vec<_>>())
+	}
+}
+
+impl IntoIterator for Program {
+	type Item = Expression;
+	type IntoIter = std::vec::IntoIter<Expression>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.expressions.into_iter()
+	}
+}
+
+impl<'a> IntoIterator for &'a Program {
+	type Item = &'a Expression;
+	type IntoIter = std::slice::Iter<'a, Expression>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.expressions.iter()
+	}
+}
+
+/// Each expression, and then each trailing comment, on a line of its own.
+impl Display for Program {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for expression in &self.expressions {
+			writeln!(f, "{}", expression)?;
+		}
+		for comment in &self.trailing_comments {
+			write_comment(f, comment)?;
+			writeln!(f)?;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod program_tests {
+	use crate::parse;
+
+	#[test]
+	fn trailing_comments() {
+		let program = parse("(a) ; b\n#| c\n|#").unwrap();
+		assert_eq!(program.len(), 1);
+		assert_eq!(program.trailing_comments, [" b", " c\n"]);
+		assert_eq!(program.to_string(), "(a)\n; b\n#| c\n|#\n");
+	}
+}
@@ -0,0 +1,178 @@
+use std::io;
+use std::io::BufRead;
+
+use super::parse_program;
+use super::ParseError;
+use super::Parser;
+use super::Program;
+
+/// Decodes the bytes of a reader into characters as they're needed, so that a program
+/// can be parsed without reading all of it into memory first.
+///
+/// An iterator can't return an error, so reading just stops at the first one, whether
+/// it came from the reader or from the bytes not being valid UTF-8. It can be taken
+/// afterwards with [`ReadChars::take_error`].
+pub struct ReadChars<R> {
+	reader: R,
+	/// The bytes of a character that has only been partly read, which happens whenever
+	/// one is split across the end of the reader's buffer.
+	pending: Vec<u8>,
+	error: Option<io::Error>,
+}
+
+impl<R: BufRead> ReadChars<R> {
+	pub fn new(reader: R) -> Self {
+		Self {
+			reader,
+			pending: Vec::with_capacity(4),
+			error: None,
+		}
+	}
+
+	/// The error that stopped reading early, if there was one.
+	pub fn take_error(&mut self) -> Option<io::Error> {
+		self.error.take()
+	}
+
+	fn fail(&mut self, error: io::Error) -> Option<char> {
+		self.pending.clear();
+		self.error = Some(error);
+		None
+	}
+}
+
+/// How many bytes long a UTF-8 encoded character is, judging by its first byte.
+fn encoded_len(first: u8) -> Option<usize> {
+	match first {
+		0x00..=0x7F => Some(1),
+		0xC2..=0xDF => Some(2),
+		0xE0..=0xEF => Some(3),
+		0xF0..=0xF4 => Some(4),
+		_ => None,
+	}
+}
+
+fn invalid_utf8() -> io::Error {
+	io::Error::new(
+		io::ErrorKind::InvalidData,
+		"stream did not contain valid UTF-8",
+	)
+}
+
+impl<R: BufRead> Iterator for ReadChars<R> {
+	type Item = char;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.error.is_some() {
+			return None;
+		}
+
+		loop {
+			let buf = match self.reader.fill_buf() {
+				Ok(buf) => buf,
+				Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+				Err(error) => return self.fail(error),
+			};
+
+			let Some(&byte) = buf.first() else {
+				// Running out in the middle of a character means the stream was cut short
+				return match self.pending.is_empty() {
+					true => None,
+					false => self.fail(invalid_utf8()),
+				};
+			};
+			self.reader.consume(1);
+			self.pending.push(byte);
+
+			let Some(len) = encoded_len(self.pending[0]) else {
+				return self.fail(invalid_utf8());
+			};
+			if self.pending.len() < len {
+				continue;
+			}
+
+			return match std::str::from_utf8(&self.pending) {
+				Ok(s) => {
+					let c = s.chars().next();
+					self.pending.clear();
+					c
+				}
+				Err(_) => self.fail(invalid_utf8()),
+			};
+		}
+	}
+}
+
+impl<'a, R: BufRead> From<&'a mut ReadChars<R>> for Parser<&'a mut ReadChars<R>> {
+	fn from(chars: &'a mut ReadChars<R>) -> Self {
+		let mut parser = Self::new(chars, 0);
+		// Offsets still count the byte order mark, just like when parsing a `str`
+		if parser.peek() == Some(&super::BYTE_ORDER_MARK) {
+			parser.lookahead.pop_front();
+			parser.location.offset += super::BYTE_ORDER_MARK.len_utf8();
+		}
+		parser.skip_shebang();
+		parser
+	}
+}
+
+/// Parses a complete program from `reader`, decoding it as it goes. If reading fails
+/// part way through, that's the error reported, rather than whatever the parser made of
+/// the program being cut short.
+pub fn parse_read_program<R: BufRead>(reader: R) -> Result<Program, ParseError> {
+	let mut chars = ReadChars::new(reader);
+	let mut parser = Parser::from(&mut chars);
+	let program = parse_program(&mut parser);
+	let location = parser.location();
+
+	match chars.take_error() {
+		Some(error) => Err(ParseError::Read {
+			message: error.to_string(),
+			location,
+		}),
+		None => program,
+	}
+}
+
+#[cfg(test)]
+mod read_chars_tests {
+	use super::*;
+	use std::io::BufReader;
+
+	#[test]
+	fn split_characters() {
+		let source = "(é 'ß' \"日本\" 😀)";
+		// A buffer of a single byte splits every character that takes more than one
+		for capacity in 1..=4 {
+			let mut chars = ReadChars::new(BufReader::with_capacity(capacity, source.as_bytes()));
+			assert_eq!((&mut chars).collect::<String>(), source);
+			assert!(chars.take_error().is_none());
+		}
+	}
+
+	#[test]
+	fn malformed() {
+		for bytes in [&b"ab\xFFcd"[..], b"ab\xC3", b"ab\xE6\x97(", b"\xC0\x80"] {
+			let mut chars = ReadChars::new(BufReader::with_capacity(1, bytes));
+			let read = (&mut chars).collect::<String>();
+			assert!(bytes.starts_with(read.as_bytes()));
+			assert_eq!(
+				chars.take_error().map(|error| error.kind()),
+				Some(io::ErrorKind::InvalidData)
+			);
+		}
+	}
+
+	#[test]
+	fn parse() {
+		let source = "\u{FEFF}(é 'ß') [\"日本 😀\" 1]";
+		let read = parse_read_program(BufReader::with_capacity(1, source.as_bytes())).unwrap();
+		assert_eq!(read, crate::parse(source).unwrap());
+	}
+
+	#[test]
+	fn parse_malformed() {
+		let error = parse_read_program(BufReader::with_capacity(1, &b"(a \xFF b)"[..]));
+		assert!(matches!(error, Err(ParseError::Read { .. })));
+	}
+}
@@ -0,0 +1,212 @@
+use std::fmt;
+use std::fmt::Debug;
+use std::ops::Deref;
+use std::ops::Range;
+
+use super::BYTE_ORDER_MARK;
+
+/// A zero-indexed position in the source.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Location {
+	pub line: u32,
+	pub column: u32,
+	/// How many bytes into the source this is, for tools that need to slice the source
+	/// rather than count lines.
+	pub offset: usize,
+}
+
+impl Location {
+	pub fn next_column(&mut self) {
+		self.column += 1;
+	}
+
+	pub fn next_columns(&mut self, columns: u32) {
+		self.column += columns;
+	}
+
+	/// Moves to the next tab stop, with stops every `tab_width` columns.
+	pub fn next_tab(&mut self, tab_width: u32) {
+		let tab_width = tab_width.max(1);
+		self.column = (self.column / tab_width + 1) * tab_width;
+	}
+
+	pub fn next_line(&mut self) {
+		self.line += 1;
+		self.column = 0;
+	}
+
+	/// Where this is in `source`, counting columns in UTF-16 code units like the
+	/// language server protocol does. Characters outside of the basic multilingual plane,
+	/// like most emoji, take up two units, and tabs are only ever one.
+	pub fn to_utf16(&self, source: &str) -> Utf16Location {
+		let before = source.get(..self.offset).unwrap_or(source);
+		let line_start = before.rfind(['\n', '\r']).map_or(0, |i| i + 1);
+		let column = before[line_start..]
+			.chars()
+			// Editors never show the byte order mark, so it doesn't take up a column
+			.filter(|&c| c != BYTE_ORDER_MARK)
+			.map(char::len_utf16)
+			.sum::<usize>();
+
+		Utf16Location {
+			line: self.line,
+			column: column as u32,
+		}
+	}
+}
+
+/// How many columns `c` takes up when shown in a terminal. Without the `unicode-width`
+/// feature, every character is assumed to take up one.
+#[cfg(feature = "unicode-width")]
+pub(crate) fn display_width(c: char) -> usize {
+	unicode_width::UnicodeWidthChar::width(c).unwrap_or(0)
+}
+
+#[cfg(not(feature = "unicode-width"))]
+pub(crate) fn display_width(_: char) -> usize {
+	1
+}
+
+/// A zero-indexed position in the source, with columns counted in UTF-16 code units.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Utf16Location {
+	pub line: u32,
+	pub column: u32,
+}
+
+/// The region of source a node was parsed from. `end` is exclusive.
+#[derive(Clone, Copy, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Span {
+	pub start: Location,
+	pub end: Location,
+}
+
+impl Span {
+	pub fn new(start: Location, end: Location) -> Self {
+		Self { start, end }
+	}
+
+	/// The byte range of the source covered by the span.
+	pub fn bytes(&self) -> Range<usize> {
+		self.start.offset..self.end.offset
+	}
+
+	/// The text of `source` covered by the span. `source` has to be what the span was
+	/// parsed from, and if the span doesn't fit inside of it, this is empty instead.
+	pub fn source_text<'a>(&self, source: &'a str) -> &'a str {
+		source.get(self.bytes()).unwrap_or_default()
+	}
+
+	/// The span with columns counted in UTF-16 code units. See [`Location::to_utf16`].
+	pub fn to_utf16(&self, source: &str) -> Range<Utf16Location> {
+		self.start.to_utf16(source)..self.end.to_utf16(source)
+	}
+}
+
+impl Debug for Span {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"{}:{}..{}:{}",
+			self.start.line, self.start.column, self.end.line, self.end.column
+		)
+	}
+}
+
+/// Pairs a node with the span of source it was parsed from.
+#[derive(Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Spanned<T> {
+	#[cfg_attr(feature = "serde", serde(flatten))]
+	pub node: T,
+	pub span: Span,
+}
+
+impl<T> Spanned<T> {
+	pub fn new(node: T, span: Span) -> Self {
+		Self { node, span }
+	}
+
+	/// The text the node was parsed from. See [`Span::source_text`].
+	pub fn source_text<'a>(&self, source: &'a str) -> &'a str {
+		self.span.source_text(source)
+	}
+}
+
+impl<T> Deref for Spanned<T> {
+	type Target = T;
+
+	fn deref(&self) -> &Self::Target {
+		&self.node
+	}
+}
+
+impl<T: Debug> Debug for Spanned<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.node.fmt(f)?;
+		write!(f, " @ {:?}", self.span)
+	}
+}
+
+#[cfg(test)]
+mod to_utf16_tests {
+	use crate::parse;
+
+	#[test]
+	fn astral_characters() {
+		let source = "(a \"😀\" b)\n(é \"😀😀\" c)";
+		let program = parse(source).unwrap();
+
+		let b = &program[0].values[2];
+		assert_eq!(b.span.start.column, 7);
+		assert_eq!(b.span.to_utf16(source).start.column, 8);
+
+		let c = &program[1].values[2];
+		assert_eq!(c.span.start.column, 8);
+		assert_eq!(c.span.to_utf16(source).start.column, 10);
+		assert_eq!(c.span.to_utf16(source).end.line, 1);
+	}
+
+	#[test]
+	fn tabs_and_byte_order_marks() {
+		let source = "\u{FEFF}\t(a)";
+		let program = parse(source).unwrap();
+		let a = &program[0].values[0];
+		assert_eq!(a.span.to_utf16(source).start.column, 2);
+	}
+}
+
+#[cfg(test)]
+mod source_text_tests {
+	use crate::parse;
+
+	#[test]
+	fn nodes() {
+		let source = "(foo)";
+		let program = parse(source).unwrap();
+		assert_eq!(program[0].source_text(source), "(foo)");
+		assert_eq!(program[0].values[0].source_text(source), "foo");
+	}
+
+	#[test]
+	fn multibyte() {
+		let source = "(é \"😀\" 'ü')";
+		let program = parse(source).unwrap();
+		let texts = program[0]
+			.values
+			.iter()
+			.map(|value| value.source_text(source))
+			.collect::<Vec<_>>();
+		assert_eq!(texts, ["é", "\"😀\"", "'ü'"]);
+	}
+
+	#[test]
+	fn other_source() {
+		let program = parse("(é a)").unwrap();
+		assert_eq!(program[0].values[1].source_text("(a)"), "");
+		assert_eq!(program[0].values[0].source_text("ab😀"), "");
+	}
+}
@@ -0,0 +1,843 @@
+use std::str::Chars;
+
+use super::phrase::*;
+use super::Expression;
+use super::ExpressionKind;
+use super::Location;
+use super::Number;
+use super::ParseError;
+use super::Parser;
+use super::Program;
+use super::Span;
+use super::Spanned;
+use super::DEFAULT_MAX_DEPTH;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+pub enum TokenKind {
+	OpenParen,
+	CloseParen,
+	OpenBracket,
+	CloseBracket,
+	OpenBrace,
+	CloseBrace,
+	Identifier(String),
+	String(String),
+	Char(char),
+	Number(Number),
+	Comment(String),
+	Keyword(String),
+	Boolean(bool),
+	Nil,
+	/// The `.` of a dotted pair, like `(a . b)`.
+	Dot,
+	/// A `#;` datum comment, which comments out the phrase after it.
+	DatumComment,
+	/// A `::type` annotation on whatever came right before it.
+	Type(String),
+	/// A quoting shorthand, like the `'` in `'x`.
+	Quote(QuoteKind),
+	/// Always the last token, so that there is somewhere to point at when the source
+	/// ends too soon.
+	Eof,
+}
+
+impl TokenKind {
+	/// The kind of expression this token begins, if it begins one.
+	pub fn opens(&self) -> Option<ExpressionKind> {
+		match self {
+			Self::OpenParen => Some(ExpressionKind::Item),
+			Self::OpenBracket => Some(ExpressionKind::List),
+			Self::OpenBrace => Some(ExpressionKind::Block),
+			_ => None,
+		}
+	}
+
+	/// The kind of expression this token ends, if it ends one.
+	pub fn closes(&self) -> Option<ExpressionKind> {
+		match self {
+			Self::CloseParen => Some(ExpressionKind::Item),
+			Self::CloseBracket => Some(ExpressionKind::List),
+			Self::CloseBrace => Some(ExpressionKind::Block),
+			_ => None,
+		}
+	}
+
+	fn from_bracket(c: char) -> Option<Self> {
+		match c {
+			'(' => Some(Self::OpenParen),
+			')' => Some(Self::CloseParen),
+			'[' => Some(Self::OpenBracket),
+			']' => Some(Self::CloseBracket),
+			'{' => Some(Self::OpenBrace),
+			'}' => Some(Self::CloseBrace),
+			_ => None,
+		}
+	}
+
+	/// The phrase this token stands for on its own, if it is one.
+	fn into_phrase(self) -> Option<Phrase> {
+		match self {
+			Self::Identifier(identifier) => Some(Phrase::Identifier(identifier)),
+			Self::String(text) => Some(Phrase::Text(text)),
+			Self::Char(c) => Some(Phrase::Char(c)),
+			Self::Number(number) => Some(Phrase::Number(number)),
+			Self::Comment(body) => Some(Phrase::Comment(body)),
+			Self::Keyword(keyword) => Some(Phrase::Keyword(keyword)),
+			Self::Boolean(boolean) => Some(Phrase::Boolean(boolean)),
+			Self::Nil => Some(Phrase::Nil),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Token {
+	pub kind: TokenKind,
+	pub span: Span,
+}
+
+impl Token {
+	pub fn new(kind: TokenKind, span: Span) -> Self {
+		Self { kind, span }
+	}
+}
+
+/// Splits `source` into tokens, without checking that they make up a valid program.
+pub fn tokenize(source: &str) -> Result<Vec<Token>, ParseError> {
+	Tokens::new(source).collect()
+}
+
+/// The tokens of some source, each scanned only once it's asked for, so that an error in
+/// one only turns up after everything before it.
+struct Tokens<'a> {
+	parser: Parser<Chars<'a>>,
+	/// Whether the last token could be followed by a type.
+	typeable: bool,
+	done: bool,
+}
+
+/// How the next token starts, which is enough to tell whether it can go where it is
+/// before scanning the rest of it.
+enum Upcoming {
+	/// A comment, or something that can only be one if it's anything at all.
+	Comment,
+	DatumComment,
+	Char(char),
+}
+
+impl<'a> Tokens<'a> {
+	fn new(source: &'a str) -> Self {
+		Self {
+			parser: Parser::from(source),
+			typeable: false,
+			done: false,
+		}
+	}
+
+	/// Skips ahead to the next token and tells how it starts, unless it's a type, which
+	/// belongs to the token before it, or there is nothing left but the end.
+	fn upcoming(&mut self) -> Result<Option<(Upcoming, Location)>, ParseError> {
+		if self.done || (self.typeable && self.parser.peek() == Some(&':')) {
+			return Ok(None);
+		}
+		// A type has to come right after the token it's on
+		self.typeable = false;
+		parse_whitespace(&mut self.parser)?;
+
+		let location = self.parser.location();
+		let upcoming = match self.parser.peek().copied() {
+			None => return Ok(None),
+			Some(_) if self.parser.at_comment() => Upcoming::Comment,
+			Some('#') if self.parser.peek_nth(1) == Some(&';') => Upcoming::DatumComment,
+			// Besides a boolean, anything starting with `#` can only be a block comment
+			Some('#') if peek_hash_boolean(&mut self.parser).is_none() => Upcoming::Comment,
+			Some(c) => Upcoming::Char(c),
+		};
+		Ok(Some((upcoming, location)))
+	}
+
+	fn scan(&mut self) -> Result<Token, ParseError> {
+		if std::mem::take(&mut self.typeable) && self.parser.peek() == Some(&':') {
+			let start = self.parser.location();
+			let name = parse_type(&mut self.parser)?;
+			return Ok(Token::new(
+				TokenKind::Type(name),
+				Span::new(start, self.parser.location()),
+			));
+		}
+
+		parse_whitespace(&mut self.parser)?;
+
+		let start = self.parser.location();
+		let Some(&c) = self.parser.peek() else {
+			self.done = true;
+			return Ok(Token::new(TokenKind::Eof, Span::new(start, start)));
+		};
+
+		if let Some(quote) = parse_quote_prefix(&mut self.parser) {
+			return Ok(Token::new(
+				TokenKind::Quote(quote),
+				Span::new(start, self.parser.location()),
+			));
+		}
+
+		let kind = match TokenKind::from_bracket(c) {
+			Some(bracket) => {
+				self.parser.next();
+				bracket
+			}
+			None if is_dot(&mut self.parser) => {
+				self.parser.next();
+				TokenKind::Dot
+			}
+			None if c == '#' && self.parser.peek_nth(1) == Some(&';') => {
+				self.parser.next();
+				self.parser.next();
+				TokenKind::DatumComment
+			}
+			None => atom_kind(parse_atom(&mut self.parser)?.node, start)?,
+		};
+
+		// Anything but an opening bracket, a dot, or a datum comment can be followed by
+		// a type
+		self.typeable =
+			kind.opens().is_none() && !matches!(kind, TokenKind::Dot | TokenKind::DatumComment);
+		Ok(Token::new(kind, Span::new(start, self.parser.location())))
+	}
+
+	/// Scans the next token as a block comment, for where a `#` can't start anything else.
+	fn next_block_comment(&mut self) -> Result<Token, ParseError> {
+		let start = self.parser.location();
+		let comment = parse_block_comment(&mut self.parser).and_then(|comment| {
+			let kind = atom_kind(comment, start)?;
+			Ok(Token::new(kind, Span::new(start, self.parser.location())))
+		});
+		self.stop_after_error(comment)
+	}
+
+	/// Nothing after an error can be trusted to be split up correctly, so it's the last
+	/// token there will be.
+	fn stop_after_error(&mut self, token: Result<Token, ParseError>) -> Result<Token, ParseError> {
+		if token.is_err() {
+			self.done = true;
+		}
+		token
+	}
+}
+
+impl Iterator for Tokens<'_> {
+	type Item = Result<Token, ParseError>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.done {
+			return None;
+		}
+		let token = self.scan();
+		Some(self.stop_after_error(token))
+	}
+}
+
+/// The kind of token an atom, scanned like any other phrase, makes up.
+fn atom_kind(atom: Phrase, start: Location) -> Result<TokenKind, ParseError> {
+	Ok(match atom {
+		Phrase::Identifier(identifier) => TokenKind::Identifier(identifier),
+		// Interning is never enabled for the tokenizer's own parser
+		Phrase::Symbol(symbol) => TokenKind::Identifier(symbol.name().to_string()),
+		Phrase::Text(text) => TokenKind::String(text),
+		Phrase::Char(c) => TokenKind::Char(c),
+		Phrase::Number(number) => TokenKind::Number(number),
+		Phrase::Comment(body) => TokenKind::Comment(body),
+		Phrase::Keyword(keyword) => TokenKind::Keyword(keyword),
+		Phrase::Boolean(boolean) => TokenKind::Boolean(boolean),
+		Phrase::Nil => TokenKind::Nil,
+		// Brackets are handled before scanning an atom, so an atom is never an expression
+		Phrase::Expression(expression) => {
+			let found = expression.kind.initiator();
+			return Err(ParseError::unexpected(Some(found), "a token", start));
+		}
+	})
+}
+
+#[cfg(test)]
+mod tokenize_tests {
+	use super::*;
+
+	fn span(start: u32, end: u32) -> Span {
+		Span::new(
+			Location {
+				line: 0,
+				column: start,
+				offset: start as usize,
+			},
+			Location {
+				line: 0,
+				column: end,
+				offset: end as usize,
+			},
+		)
+	}
+
+	#[test]
+	fn item() {
+		assert_eq!(
+			tokenize("(a 1)").unwrap(),
+			vec![
+				Token::new(TokenKind::OpenParen, span(0, 1)),
+				Token::new(TokenKind::Identifier("a".to_string()), span(1, 2)),
+				Token::new(TokenKind::Number(Number::Integer(1)), span(3, 4)),
+				Token::new(TokenKind::CloseParen, span(4, 5)),
+				Token::new(TokenKind::Eof, span(5, 5)),
+			]
+		);
+	}
+
+	#[test]
+	fn types() {
+		assert_eq!(
+			tokenize("a::int")
+				.unwrap()
+				.into_iter()
+				.map(|token| token.kind)
+				.collect::<Vec<_>>(),
+			vec![
+				TokenKind::Identifier("a".to_string()),
+				TokenKind::Type("int".to_string()),
+				TokenKind::Eof,
+			]
+		);
+	}
+
+	#[test]
+	fn unbalanced() {
+		// Whether brackets match up isn't the lexer's problem
+		assert!(tokenize(")(]").is_ok());
+	}
+}
+
+/// An expression that has been opened, but not yet closed.
+struct Open {
+	kind: ExpressionKind,
+	start: Location,
+	values: Vec<Spanned<Phrase>>,
+	leading_comments: Vec<String>,
+	/// Whether a `.` has been seen, so that the next value becomes the tail.
+	dotted: bool,
+	tail: Option<Box<Spanned<Phrase>>>,
+	/// How many more values `#;` comments have asked to be dropped.
+	skipping: usize,
+	/// Whether this is the expansion of a quoting shorthand, which closes by itself as
+	/// soon as it has something to quote.
+	quote: bool,
+}
+
+impl Open {
+	fn close(self, end: Location) -> Spanned<Phrase> {
+		let span = Span::new(self.start, end);
+		let expression = Expression {
+			kind: self.kind,
+			values: self.values,
+			leading_comments: self.leading_comments,
+			tail: self.tail,
+			span,
+		};
+		Spanned::new(Phrase::Expression(expression), span)
+	}
+}
+
+/// Adds `value` to the innermost open expression, closing any quotes that were waiting
+/// on it, unless it was commented out. Hands `value` back if there was nothing open to
+/// add it to.
+fn push_value(open: &mut Vec<Open>, mut value: Spanned<Phrase>) -> Option<Spanned<Phrase>> {
+	while let Some(mut parent) = open.pop() {
+		if parent.skipping > 0 {
+			parent.skipping -= 1;
+			open.push(parent);
+			return None;
+		}
+
+		let end = value.span.end;
+		match parent.dotted {
+			true => parent.tail = Some(Box::new(value)),
+			false => parent.values.push(value),
+		}
+		if !parent.quote {
+			open.push(parent);
+			return None;
+		}
+		value = parent.close(end);
+	}
+	Some(value)
+}
+
+/// Parses a complete program from `source` by first splitting it into tokens.
+pub fn parse_tokens(source: &str) -> Result<Program, ParseError> {
+	// Expressions are built up with a stack rather than by recursing, but are still
+	// limited in depth by default, so that nothing else recurses too deeply over them
+	// later.
+	parse_tokens_with_max_depth(source, Some(DEFAULT_MAX_DEPTH), None)
+}
+
+/// Parses a complete program from `source` just like [`parse_tokens`], but without any
+/// limit on how deeply expressions may be nested.
+pub fn parse_tokens_unbounded(source: &str) -> Result<Program, ParseError> {
+	parse_tokens_with_max_depth(source, None, None)
+}
+
+/// Splits a complete program from `source` into its tokens, failing in exactly the same
+/// way as [`parse_tokens`]. Unlike [`tokenize`], which can't tell, a `:` only begins a
+/// type where the program could have one.
+pub(super) fn tokenize_program(source: &str) -> Result<Vec<Token>, ParseError> {
+	let mut tokens = vec![];
+	parse_tokens_with_max_depth(source, Some(DEFAULT_MAX_DEPTH), Some(&mut tokens))?;
+	Ok(tokens)
+}
+
+/// Parses a complete program from `source`, also keeping each of its tokens in `scanned`
+/// if asked to.
+fn parse_tokens_with_max_depth(
+	source: &str,
+	max_depth: Option<usize>,
+	mut scanned: Option<&mut Vec<Token>>,
+) -> Result<Program, ParseError> {
+	let mut program = vec![];
+	let mut open: Vec<Open> = vec![];
+	// Comments at the top level, waiting for an expression to attach to
+	let mut comments = vec![];
+	// How many top-level expressions `#;` comments have asked to be dropped, and whether
+	// the last token finished one, so that any type after it goes along with it
+	let mut skipping = 0;
+	let mut dropped = false;
+
+	// Whether the last token was a comment dropped along with what a `#;` commented out
+	let mut skipped_comment = false;
+
+	let mut tokens = Tokens::new(source);
+	loop {
+		// Like in the character parser, only a phrase inside of an expression, or one that
+		// was commented out, can have a type
+		let typed = !open.is_empty() || dropped;
+		tokens.typeable &= !std::mem::take(&mut skipped_comment) && typed;
+
+		let token = match tokens.upcoming()? {
+			// Like in the character parser, a `#` at the top level can only be a comment
+			Some((Upcoming::Char('#'), _)) if open.is_empty() && skipping == 0 => {
+				Some(tokens.next_block_comment())
+			}
+			Some((upcoming, location)) => {
+				check_upcoming(&open, skipping, !program.is_empty(), upcoming, location)?;
+				tokens.next()
+			}
+			None => tokens.next(),
+		};
+		let Some(token) = token.transpose()? else {
+			break;
+		};
+		if let Some(scanned) = scanned.as_deref_mut() {
+			scanned.push(token.clone());
+		}
+		let location = token.span.start;
+		let dropped_before = std::mem::take(&mut dropped);
+
+		// Once a pair has its tail, only the end of it may follow, and until then,
+		// something has to be there to be its tail
+		if let Some(pair) = open
+			.last()
+			.filter(|expression| expression.dotted && expression.skipping == 0)
+		{
+			let malformed = match pair.tail {
+				Some(_) => {
+					!matches!(
+						token.kind,
+						TokenKind::Type(_) | TokenKind::DatumComment | TokenKind::Eof
+					) && token.kind.closes().is_none()
+				}
+				None => {
+					matches!(token.kind, TokenKind::Eof | TokenKind::Comment(_))
+						|| token.kind.closes().is_some()
+				}
+			};
+			if malformed {
+				return Err(ParseError::MalformedPair { location });
+			}
+		}
+
+		let skipping_here = match open.last_mut() {
+			Some(expression) => &mut expression.skipping,
+			None => &mut skipping,
+		};
+		match token.kind {
+			TokenKind::DatumComment => {
+				*skipping_here += 1;
+				continue;
+			}
+			TokenKind::Eof if *skipping_here > 0 => {
+				return Err(ParseError::UnexpectedEof {
+					expected: "a phrase to comment out",
+					location,
+				});
+			}
+			ref kind if *skipping_here > 0 && kind.closes().is_some() => {
+				return Err(ParseError::unexpected(
+					char_at(source, location),
+					"a phrase to comment out",
+					location,
+				));
+			}
+			_ => {}
+		}
+
+		let opens = match &token.kind {
+			TokenKind::Quote(quote) if !open.is_empty() || skipping > 0 => {
+				Some((ExpressionKind::Item, Some(*quote)))
+			}
+			kind => kind.opens().map(|kind| (kind, None)),
+		};
+		if let Some((kind, quote)) = opens {
+			if let Some(max_depth) = max_depth.filter(|&max_depth| open.len() >= max_depth) {
+				return Err(ParseError::NestingTooDeep {
+					max_depth,
+					location,
+				});
+			}
+			let leading_comments = match open.is_empty() && skipping == 0 {
+				true => comments
+					.drain(..)
+					.filter_map(|comment: Spanned<Phrase>| match comment.node {
+						Phrase::Comment(body) => Some(body),
+						_ => None,
+					})
+					.collect(),
+				false => vec![],
+			};
+			let values = match quote {
+				Some(quote) => vec![Spanned::new(
+					Phrase::Identifier(quote.name().to_string()),
+					token.span,
+				)],
+				None => vec![],
+			};
+			open.push(Open {
+				kind,
+				start: location,
+				values,
+				leading_comments,
+				dotted: false,
+				tail: None,
+				skipping: 0,
+				quote: quote.is_some(),
+			});
+			continue;
+		}
+
+		if let Some(kind) = token.kind.closes() {
+			let Some(expression) = open.pop() else {
+				return Err(ParseError::UnmatchedDelimiter {
+					found: kind.terminator(),
+					location,
+				});
+			};
+			if expression.quote {
+				return Err(ParseError::unexpected(
+					Some(kind.terminator()),
+					EXPECTED_PHRASE,
+					location,
+				));
+			}
+			if kind != expression.kind {
+				return Err(ParseError::unterminated(
+					&expression.kind,
+					Some(kind.terminator()),
+					expression.start,
+					location,
+				));
+			}
+
+			let value = expression.close(token.span.end);
+			if let Some(value) = push_value(&mut open, value) {
+				push_top_level(&mut program, &mut skipping, &mut dropped, value);
+			}
+			continue;
+		}
+
+		match (token.kind, open.last_mut()) {
+			(TokenKind::Eof, Some(expression)) if expression.quote => {
+				return Err(ParseError::UnexpectedEof {
+					expected: EXPECTED_PHRASE,
+					location,
+				});
+			}
+			(TokenKind::Eof, Some(expression)) => {
+				return Err(ParseError::unterminated(
+					&expression.kind,
+					None,
+					expression.start,
+					location,
+				));
+			}
+			(TokenKind::Eof, None) => break,
+			(TokenKind::Type(_), Some(_)) => {}
+			(TokenKind::Type(_), None) if dropped_before => {}
+			// With nothing before it, a `.` is an identifier rather than part of a pair
+			(TokenKind::Dot, Some(expression))
+				if !expression.quote
+					&& expression.skipping == 0
+					&& expression
+						.values
+						.iter()
+						.all(|value| matches!(value.node, Phrase::Comment(_))) =>
+			{
+				expression.values.push(Spanned::new(
+					Phrase::Identifier(".".to_string()),
+					token.span,
+				));
+			}
+			(TokenKind::Dot, Some(expression)) => {
+				if expression.kind != ExpressionKind::Item
+					|| expression.quote
+					|| expression.dotted
+					|| expression.skipping > 0
+				{
+					return Err(ParseError::MalformedPair { location });
+				}
+				expression.dotted = true;
+			}
+			// Comments don't count as what a datum comment comments out
+			(TokenKind::Comment(_), Some(expression)) if expression.skipping > 0 => {
+				skipped_comment = true;
+			}
+			(TokenKind::Comment(_), None) if skipping > 0 => skipped_comment = true,
+			(TokenKind::Comment(body), None) => {
+				comments.push(Spanned::new(Phrase::Comment(body), token.span));
+			}
+			(TokenKind::Dot, None) if skipping > 0 => {
+				return Err(ParseError::MalformedPair { location });
+			}
+			(kind, None) if skipping > 0 => {
+				if let Some(phrase) = kind.into_phrase() {
+					push_top_level(
+						&mut program,
+						&mut skipping,
+						&mut dropped,
+						Spanned::new(phrase, token.span),
+					);
+				}
+			}
+			(_, None) => {
+				return Err(ParseError::not_an_expression(
+					char_at(source, location),
+					!program.is_empty(),
+					location,
+				));
+			}
+			(kind, Some(_)) => {
+				let value = kind
+					.into_phrase()
+					.and_then(|phrase| push_value(&mut open, Spanned::new(phrase, token.span)));
+				// Only a commented out quote can finish at the top level like this
+				if let Some(value) = value {
+					push_top_level(&mut program, &mut skipping, &mut dropped, value);
+				}
+			}
+		}
+	}
+
+	let trailing_comments = comments
+		.into_iter()
+		.filter_map(|comment| match comment.node {
+			Phrase::Comment(body) => Some(body),
+			_ => None,
+		})
+		.collect();
+	Ok(Program::new(program, trailing_comments))
+}
+
+/// Rejects the next token before it's scanned, if nothing starting the way it does could
+/// go where it is. Like the character parser, this reports a token that doesn't belong
+/// as such, rather than for anything wrong inside of it.
+fn check_upcoming(
+	open: &[Open],
+	skipping: usize,
+	trailing: bool,
+	upcoming: Upcoming,
+	location: Location,
+) -> Result<(), ParseError> {
+	match (open.last(), upcoming) {
+		(None, Upcoming::Char(c))
+			if skipping == 0 && ExpressionKind::from_initiator(c).is_none() =>
+		{
+			Err(ParseError::not_an_expression(Some(c), trailing, location))
+		}
+		// Once a pair has its tail, only the end of it may follow, and until then, a
+		// comment can't be its tail
+		(Some(pair), upcoming) if pair.dotted && pair.skipping == 0 => {
+			let malformed = match (&pair.tail, upcoming) {
+				(Some(_), Upcoming::Char(c)) => !matches!(c, ')' | ']' | '}'),
+				(Some(_), Upcoming::Comment) | (None, Upcoming::Comment) => true,
+				(_, Upcoming::DatumComment) | (None, Upcoming::Char(_)) => false,
+			};
+			match malformed {
+				true => Err(ParseError::MalformedPair { location }),
+				false => Ok(()),
+			}
+		}
+		_ => Ok(()),
+	}
+}
+
+/// Adds a finished top-level value to `program`, unless a datum comment asked for it to
+/// be dropped.
+fn push_top_level(
+	program: &mut Vec<Expression>,
+	skipping: &mut usize,
+	dropped: &mut bool,
+	value: Spanned<Phrase>,
+) {
+	if *skipping > 0 {
+		*skipping -= 1;
+		*dropped = true;
+	} else if let Phrase::Expression(expression) = value.node {
+		program.push(expression);
+	}
+}
+
+/// Finds the character at `location`, counting lines the same way the parser does.
+fn char_at(source: &str, location: Location) -> Option<char> {
+	let mut parser = Parser::from(source);
+	while parser.location() != location {
+		parser.next()?;
+	}
+	parser.peek().copied()
+}
+
+#[cfg(test)]
+mod parse_tokens_tests {
+	use super::*;
+	use crate::parser::parse_program;
+
+	#[test]
+	fn matches_character_parser() {
+		for source in [
+			"(a) [b 1 2.5] {c \"d\" 'e'}",
+			"; doc\n(func add (a::int b) ; comment\n (+ a b))",
+			"#| block |#\n[true false nil :label]\n; at the end",
+			"(a (b (c)) [d {e}])",
+			"(list 'a `(b ,c ,@d) ''e 'f' '\\'')",
+			"(a . b) (c d\n . (e f)) (g . 'h) (i . .5) (j . l::int)",
+			"(a #; b c) (#; #; d e f) (g #; (h 'i) ; j\n k)",
+			"; doc\n#; (a) (b) #; c::int #; 'd (e . #; f g #; h)",
+			"(a #; ; comment\n b c) #;\n; comment\n(d)",
+			"#; (a)::int (b)",
+			"(#t #f #| #t |# #; #true #false . #t)",
+			"(|a b| |c\\|d|::int (| e))",
+			"(\"across\n lines\" \"joined \\\n  together\")",
+			"(. a b) (; c\n . a) [. a] (. . b) (#; a . b) (a '(. b)) (.5 . c)",
+			"#; #| a |#:b (c) #; #| d |# #; e::int f",
+			"",
+		] {
+			assert_eq!(
+				parse_tokens(source).unwrap(),
+				parse_program(&mut source.into()).unwrap()
+			);
+			assert_eq!(
+				parse_tokens_unbounded(source).unwrap(),
+				parse_tokens(source).unwrap()
+			);
+		}
+	}
+
+	#[test]
+	fn same_errors() {
+		for source in [
+			"(a",
+			"(a]",
+			")",
+			"a",
+			"(a) b",
+			"(a) ; b\n c",
+			"(a))",
+			"]",
+			"(a)::int",
+			"(a\n  (b c]",
+			"'a",
+			"(a ')",
+			"(a '",
+			"(a . b . c)",
+			"(a . b c)",
+			"(. . a . b)",
+			"(a .)",
+			"(a . #| b |#)",
+			"(a #tx)",
+			"(a 123abc)",
+			"(a . ; c\n b)",
+			"(a . b ; c\n)",
+			"[a . b]",
+			"(a '. b)",
+			". a",
+			"(a . ')",
+			"(a . (b c]",
+			"(a #;)",
+			"(a #;",
+			"#; ; only a comment",
+			"#; )",
+			"(a #; . b)",
+			"'#;",
+			"#; . a",
+			"(a)::int",
+			"(|a b",
+			"(a \0)",
+			"(a) \u{7f}",
+			"(a]\n(b \"unterminated",
+			")\n1abc",
+			"x 'ab'",
+			"(a) \"b",
+			"(a . b",
+			"(a . #x)",
+			"(a . b #;'c",
+			"(a . b #| c",
+			"(a\n:k",
+			"():k",
+			"; c\n:k",
+			"#t",
+			"#x",
+		] {
+			assert_eq!(
+				parse_tokens(source).unwrap_err(),
+				parse_program(&mut source.into()).unwrap_err()
+			);
+			assert_eq!(
+				parse_tokens_unbounded(source).unwrap_err(),
+				parse_tokens(source).unwrap_err()
+			);
+		}
+	}
+
+	#[test]
+	fn nesting_too_deep() {
+		let deep = "(".repeat(10_000);
+		assert!(matches!(
+			parse_tokens(&deep),
+			Err(ParseError::NestingTooDeep { .. })
+		));
+	}
+
+	#[test]
+	fn unbounded() {
+		const DEPTH: usize = 1_000_000;
+		let deep = format!("{}{}", "(".repeat(DEPTH), ")".repeat(DEPTH));
+		let program = parse_tokens_unbounded(&deep).unwrap();
+		assert_eq!(program.len(), 1);
+
+		// Checking the result has to avoid recursing just as much as parsing it did
+		let mut depth = 1;
+		let mut expression = &program[0];
+		while let Some(Phrase::Expression(inner)) = expression.values.first().map(|v| &v.node) {
+			depth += 1;
+			expression = inner;
+		}
+		assert_eq!(depth, DEPTH);
+		assert!(expression.values.is_empty());
+	}
+}
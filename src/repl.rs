@@ -0,0 +1,103 @@
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+use yall::eval;
+use yall::eval::Env;
+
+use crate::options::Options;
+
+/// Reads forms from stdin, evaluating each one and printing its value, until stdin
+/// is closed. Errors are printed, but never end the loop.
+pub fn run(options: &Options) -> io::Result<()> {
+	let stdin = io::stdin();
+	let mut stdin = stdin.lock();
+	let mut env = Env::new();
+	let mut source = String::new();
+
+	loop {
+		match source.is_empty() {
+			true => print!("> "),
+			false => print!(". "),
+		}
+		io::stdout().flush()?;
+
+		if stdin.read_line(&mut source)? == 0 {
+			println!();
+			return Ok(());
+		}
+
+		if !is_complete(&source, options) {
+			continue;
+		}
+		if source.trim().is_empty() {
+			source.clear();
+			continue;
+		}
+
+		let mut parser = options.parser(&source);
+		match yall::parser::parse_program(&mut parser) {
+			Ok(program) if options.debug_parser => println!("{:#?}", program),
+			Ok(program) => match eval::eval_program(&program, &mut env) {
+				Ok(value) => println!("{}", value),
+				Err(error) => eprintln!("error: {}", error),
+			},
+			Err(error) => eprintln!("{}", error.render(&source)),
+		}
+		source.clear();
+	}
+}
+
+/// Whether `source` holds only complete forms, so that it's ready to be parsed with
+/// `options`. Source that fails to parse is still complete, unless the only problem is
+/// that it ended too early, because reading more wouldn't fix it.
+pub fn is_complete(source: &str, options: &Options) -> bool {
+	!yall::parser::parse_program(&mut options.parser(source)).is_err_and(|error| error.is_eof())
+}
+
+#[cfg(test)]
+mod is_complete_tests {
+	use super::*;
+	use yall::ExpressionKind;
+
+	#[test]
+	fn balanced() {
+		let defaults = Options::builder().build().unwrap();
+		assert!(is_complete("", &defaults));
+		assert!(is_complete("(+ 1 2)\n", &defaults));
+		assert!(is_complete("[1 2] {3}", &defaults));
+		assert!(!is_complete("(+ 1\n", &defaults));
+		assert!(!is_complete("(if true {\n  1\n", &defaults));
+		assert!(is_complete("(if true {\n  1\n} 2)\n", &defaults));
+	}
+
+	#[test]
+	fn unterminated() {
+		let defaults = Options::builder().build().unwrap();
+		assert!(!is_complete("(print \"hello\n", &defaults));
+		assert!(!is_complete("#| a comment\n", &defaults));
+		assert!(is_complete("(print \"(\") ; (\n", &defaults));
+	}
+
+	#[test]
+	fn errors() {
+		let defaults = Options::builder().build().unwrap();
+		// These can't be fixed by reading more, so they should be reported right away
+		assert!(is_complete("(a]\n", &defaults));
+		assert!(is_complete(")\n", &defaults));
+		assert!(is_complete("'ab'\n", &defaults));
+	}
+
+	#[test]
+	fn options() {
+		// What's complete depends on how the source will actually be parsed
+		let hash_comments = Options::builder().comment_prefix("#").build().unwrap();
+		assert!(!is_complete("(a # c (\n", &hash_comments));
+		assert!(is_complete("(a # c (\n)\n", &hash_comments));
+
+		let parens = Options::builder()
+			.brackets(vec![ExpressionKind::Item])
+			.build()
+			.unwrap();
+		assert!(is_complete("(a [\n", &parens));
+	}
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+use std::fmt::Display;
+use yall::visit;
+use yall::Expression;
+use yall::Number;
+use yall::Program;
+use yall::Span;
+use yall::Visitor;
+
+/// Counts of what a program is made of, for a quick look at it without printing all of it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Stats {
+	pub expressions: usize,
+	pub nodes: usize,
+	pub identifiers: usize,
+	pub strings: usize,
+	pub numbers: usize,
+	pub comments: usize,
+}
+
+impl Stats {
+	pub fn collect(program: &Program) -> Self {
+		let mut stats = Stats {
+			expressions: program.len(),
+			nodes: yall::node_count(program),
+			comments: program.trailing_comments.len(),
+			..Default::default()
+		};
+		visit::walk(&mut stats, program);
+		stats
+	}
+}
+
+impl Visitor for Stats {
+	fn visit_expression(&mut self, expression: &Expression) {
+		self.comments += expression.leading_comments.len();
+		visit::walk_expression(self, expression);
+	}
+
+	fn visit_identifier(&mut self, _: &str, _: Span) {
+		self.identifiers += 1;
+	}
+
+	fn visit_text(&mut self, _: &str, _: Span) {
+		self.strings += 1;
+	}
+
+	fn visit_number(&mut self, _: Number, _: Span) {
+		self.numbers += 1;
+	}
+
+	fn visit_comment(&mut self, _: &str, _: Span) {
+		self.comments += 1;
+	}
+}
+
+/// One metric per line, always in the same order, so that it's easy to pick out with
+/// other tools.
+impl Display for Stats {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "expressions: {}", self.expressions)?;
+		writeln!(f, "nodes: {}", self.nodes)?;
+		writeln!(f, "identifiers: {}", self.identifiers)?;
+		writeln!(f, "strings: {}", self.strings)?;
+		writeln!(f, "numbers: {}", self.numbers)?;
+		writeln!(f, "comments: {}", self.comments)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn collect() {
+		let program = yall::parse(
+			"; doc\n(func add (a b) ; comment\n (+ a b 1))\n[\"x\" 2.5 :k]\n; at the end",
+		)
+		.unwrap();
+		let stats = Stats::collect(&program);
+		assert_eq!(
+			stats,
+			Stats {
+				expressions: 2,
+				nodes: 16,
+				identifiers: 7,
+				strings: 1,
+				numbers: 2,
+				comments: 3,
+			}
+		);
+		assert_eq!(
+			stats.to_string(),
+			"expressions: 2\nnodes: 16\nidentifiers: 7\nstrings: 1\nnumbers: 2\ncomments: 3\n"
+		);
+	}
+}
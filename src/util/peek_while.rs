@@ -1,38 +1,38 @@
-use std::fmt::Debug;
-use std::iter::Peekable;
+use crate::parser::Parser;
 
 pub struct PeekWhile<'a, I, P>
 where
-	I: Iterator,
-	P: FnMut(&I::Item) -> bool,
+	I: Iterator<Item = char>,
+	P: FnMut(&char) -> bool,
 {
-	peekable: &'a mut Peekable<I>,
+	parser: &'a mut Parser<I>,
 	pred: P,
 }
 
 impl<'a, I, P> Iterator for PeekWhile<'a, I, P>
 where
-	I: Iterator,
-	P: FnMut(&I::Item) -> bool,
-	I::Item: Debug,
+	I: Iterator<Item = char>,
+	P: FnMut(&char) -> bool,
 {
-	type Item = I::Item;
+	type Item = char;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		let peeked = self.peekable.peek()?;
+		let peeked = self.parser.peek()?;
 
+		// Consume through the parser, rather than the underlying iterator, so that its
+		// location stays accurate.
 		if (self.pred)(peeked) {
-			self.peekable.next()
+			self.parser.next()
 		} else {
 			None
 		}
 	}
 }
 
-pub fn peek_while<I, P>(peekable: &mut Peekable<I>, pred: P) -> PeekWhile<I, P>
+pub fn peek_while<I, P>(parser: &mut Parser<I>, pred: P) -> PeekWhile<'_, I, P>
 where
-	I: Iterator,
-	P: FnMut(&I::Item) -> bool,
+	I: Iterator<Item = char>,
+	P: FnMut(&char) -> bool,
 {
-	PeekWhile { peekable, pred }
+	PeekWhile { parser, pred }
 }
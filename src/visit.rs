@@ -0,0 +1,125 @@
+//! Traversal of a parsed program, for analyses that only care about some of its nodes.
+
+use crate::Expression;
+use crate::Number;
+use crate::Phrase;
+use crate::Span;
+use crate::Spanned;
+
+/// Visits the nodes of a program. Every method does nothing but keep walking by default,
+/// so implementors only need to override the ones they care about. An overridden
+/// `visit_expression` or `visit_phrase` can call `walk_expression` or `walk_phrase` to
+/// keep walking into its children.
+#[allow(unused_variables)]
+pub trait Visitor {
+	/// Leading comments aren't visited separately, but are available here, as
+	/// `expression.leading_comments`.
+	fn visit_expression(&mut self, expression: &Expression) {
+		walk_expression(self, expression);
+	}
+
+	fn visit_phrase(&mut self, phrase: &Spanned<Phrase>) {
+		walk_phrase(self, phrase);
+	}
+
+	fn visit_identifier(&mut self, name: &str, span: Span) {}
+
+	fn visit_text(&mut self, text: &str, span: Span) {}
+
+	fn visit_char(&mut self, c: char, span: Span) {}
+
+	fn visit_number(&mut self, number: Number, span: Span) {}
+
+	fn visit_comment(&mut self, body: &str, span: Span) {}
+
+	fn visit_keyword(&mut self, name: &str, span: Span) {}
+
+	fn visit_boolean(&mut self, boolean: bool, span: Span) {}
+
+	fn visit_nil(&mut self, span: Span) {}
+}
+
+/// Visits every expression of `program` in order.
+pub fn walk<V>(visitor: &mut V, program: &[Expression])
+where
+	V: Visitor + ?Sized,
+{
+	for expression in program {
+		visitor.visit_expression(expression);
+	}
+}
+
+/// Visits each of the values of `expression`, and then its tail if it's a dotted pair.
+pub fn walk_expression<V>(visitor: &mut V, expression: &Expression)
+where
+	V: Visitor + ?Sized,
+{
+	for value in &expression.values {
+		visitor.visit_phrase(value);
+	}
+	if let Some(tail) = &expression.tail {
+		visitor.visit_phrase(tail);
+	}
+}
+
+/// Passes `phrase` along to the hook for its variant, or into `visit_expression` if
+/// it's an expression.
+pub fn walk_phrase<V>(visitor: &mut V, phrase: &Spanned<Phrase>)
+where
+	V: Visitor + ?Sized,
+{
+	let span = phrase.span;
+	match &phrase.node {
+		Phrase::Expression(expression) => visitor.visit_expression(expression),
+		Phrase::Identifier(name) => visitor.visit_identifier(name, span),
+		Phrase::Symbol(symbol) => visitor.visit_identifier(symbol.name(), span),
+		Phrase::Text(text) => visitor.visit_text(text, span),
+		Phrase::Char(c) => visitor.visit_char(*c, span),
+		Phrase::Number(number) => visitor.visit_number(*number, span),
+		Phrase::Comment(body) => visitor.visit_comment(body, span),
+		Phrase::Keyword(name) => visitor.visit_keyword(name, span),
+		Phrase::Boolean(boolean) => visitor.visit_boolean(*boolean, span),
+		Phrase::Nil => visitor.visit_nil(span),
+	}
+}
+
+#[cfg(test)]
+mod walk_tests {
+	use super::*;
+	use crate::parse;
+
+	#[derive(Default)]
+	struct IdentifierCounter {
+		identifiers: usize,
+	}
+
+	impl Visitor for IdentifierCounter {
+		fn visit_identifier(&mut self, _: &str, _: Span) {
+			self.identifiers += 1;
+		}
+	}
+
+	#[test]
+	fn count_identifiers() {
+		let program = parse("(func add (a b) {(+ a b)}) ; add\n[x 1 \"y\" :z 'q]").unwrap();
+		let mut counter = IdentifierCounter::default();
+		walk(&mut counter, &program);
+		// `'q` is shorthand for `(quote q)`, so it counts twice
+		assert_eq!(counter.identifiers, 10);
+	}
+
+	#[test]
+	fn skip_children() {
+		struct TopLevel(usize);
+
+		impl Visitor for TopLevel {
+			fn visit_expression(&mut self, _: &Expression) {
+				self.0 += 1;
+			}
+		}
+
+		let mut visitor = TopLevel(0);
+		walk(&mut visitor, &parse("(a (b (c))) [d]").unwrap());
+		assert_eq!(visitor.0, 2);
+	}
+}
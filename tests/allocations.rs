@@ -0,0 +1,51 @@
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::fs;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+/// Counts every allocation made, so that the parsers can be compared by how many they
+/// each need.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		System.dealloc(ptr, layout)
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+	let before = ALLOCATIONS.load(Ordering::Relaxed);
+	let result = f();
+	(result, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+// This is the only test in this file, so nothing else can allocate while it's counting.
+#[test]
+fn borrowed_parse_allocates_less() {
+	let source = fs::read_to_string("./tests/testdata/basic_w_comments.yall")
+		.unwrap()
+		.repeat(1000);
+
+	let (owned, owned_allocations) = count_allocations(|| yall::parse(&source).unwrap());
+	let (borrowed, borrowed_allocations) =
+		count_allocations(|| yall::parse_borrowed(&source).unwrap());
+	println!(
+		"owned: {} allocations, borrowed: {} allocations",
+		owned_allocations, borrowed_allocations
+	);
+
+	assert_eq!(borrowed.into_owned(), owned);
+	assert!(borrowed_allocations * 2 < owned_allocations);
+}
@@ -0,0 +1,130 @@
+//! Each way of parsing a program has its own copy of the grammar, so this checks them all
+//! against each other on lots of small random programs, to catch any of them drifting
+//! away from the rest in what they accept, what they build, or which error they report.
+
+use yall::parser::parse_program;
+use yall::ParseError;
+use yall::Program;
+
+/// What random programs get pieced together from. Everything here is either part of the
+/// grammar, or something awkward to find right next to it.
+const FRAGMENTS: &[&str] = &[
+	"(", ")", "[", "]", "{", "}", " ", "\n", "\r", "\t", "\0", "a", "ab", "x", "e", "é", "1",
+	"2.5", "1e400", "0x", "+1", "-", ".", " . ", "(a . b", "'", "`", ",", "@", "\"", "r\"", "r#\"",
+	"\"#", "'\\n'", "#\\a", "\\", "\\|", "|", "#", "#;", "#|", "|#", ";", ":", "::", ":k", "::int",
+	"#t", "true", "nil",
+];
+
+/// What goes inside of the well-formed programs that get built, before they're mangled.
+const ATOMS: &[&str] = &[
+	"a",
+	"ab",
+	"-",
+	"+1",
+	"2.5",
+	":k",
+	"\"b\"",
+	"'c'",
+	"#t",
+	"nil",
+	"|d e|",
+	"r\"f\"",
+	"'g",
+	"`(h ,i ,@j)",
+	"k::int",
+	"#; l",
+	"; m\n",
+	"#| n |#",
+];
+
+/// A tiny xorshift generator, so that every run checks the same programs.
+struct Random(u64);
+
+impl Random {
+	fn next(&mut self, below: usize) -> usize {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		(self.0 % below as u64) as usize
+	}
+}
+
+/// Writes a random expression, nesting others inside of it until `depth` runs out.
+fn expression(random: &mut Random, depth: usize, source: &mut String) {
+	let (initiator, terminator) = [('(', ')'), ('[', ']'), ('{', '}')][random.next(3)];
+	source.push(initiator);
+	for _ in 0..random.next(4) {
+		match random.next(3) {
+			0 if depth > 0 => expression(random, depth - 1, source),
+			_ => source.push_str(ATOMS[random.next(ATOMS.len())]),
+		}
+		source.push_str([" ", "\n\t"][random.next(2)]);
+	}
+	if initiator == '(' && random.next(4) == 0 {
+		source.push_str(". ");
+		source.push_str(ATOMS[random.next(ATOMS.len())]);
+	}
+	source.push(terminator);
+}
+
+fn check(source: &str) {
+	let expected: Result<Program, ParseError> = parse_program(&mut source.into());
+	assert_eq!(yall::parse(source), expected, "parse {:?}", source);
+	assert_eq!(
+		yall::parse_unbounded(source),
+		expected,
+		"parse_unbounded {:?}",
+		source
+	);
+	assert_eq!(
+		yall::parse_borrowed(source).map(|program| program.into_owned()),
+		expected,
+		"parse_borrowed {:?}",
+		source
+	);
+	assert_eq!(
+		yall::parse_reader(source.as_bytes()),
+		expected,
+		"parse_reader {:?}",
+		source
+	);
+	assert_eq!(
+		yall::parse_lossless(source).err(),
+		expected.err(),
+		"parse_lossless {:?}",
+		source
+	);
+}
+
+#[test]
+fn random_programs() {
+	let mut random = Random(0x2545_f491_4f6c_dd1d);
+	for _ in 0..50_000 {
+		let len = random.next(20);
+		let source = (0..len)
+			.map(|_| FRAGMENTS[random.next(FRAGMENTS.len())])
+			.collect::<String>();
+		check(&source);
+	}
+}
+
+#[test]
+fn mangled_programs() {
+	let mut random = Random(0x9e37_79b9_7f4a_7c15);
+	for _ in 0..10_000 {
+		let mut source = String::new();
+		for _ in 0..random.next(3) + 1 {
+			expression(&mut random, 3, &mut source);
+			source.push('\n');
+		}
+		check(&source);
+
+		// Then break it somewhere, to see that each parser notices in the same place
+		let mut at = random.next(source.len() + 1);
+		while !source.is_char_boundary(at) {
+			at -= 1;
+		}
+		source.insert_str(at, FRAGMENTS[random.next(FRAGMENTS.len())]);
+		check(&source);
+	}
+}
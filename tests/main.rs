@@ -1,4 +1,6 @@
+use std::io::Write;
 use std::process::Command;
+use std::process::Stdio;
 
 mod testing;
 use testing::EXE;
@@ -40,6 +42,18 @@ fn parse_basic_w_comments() {
 	snapshot!("./tests/testdata/basic_w_comments.yall.out", result, stdout);
 }
 
+#[test]
+fn parse_block_comment() {
+	testing::before();
+
+	let result = Command::new(EXE)
+		.args(["-p", "./tests/testdata/block_comment.yall"])
+		.output()
+		.unwrap();
+
+	snapshot!("./tests/testdata/block_comment.yall.out", result, stdout);
+}
+
 #[test]
 fn parse_comment() {
 	testing::before();
@@ -93,15 +107,27 @@ fn parse_field_accessor() {
 }
 
 #[test]
-fn parse_label() {
+fn parse_keyword() {
 	testing::before();
 
 	let result = Command::new(EXE)
-		.args(["-p", "./tests/testdata/label.yall"])
+		.args(["-p", "./tests/testdata/keyword.yall"])
 		.output()
 		.unwrap();
 
-	snapshot!("./tests/testdata/label.yall.out", result, stdout);
+	snapshot!("./tests/testdata/keyword.yall.out", result, stdout);
+}
+
+#[test]
+fn parse_operators() {
+	testing::before();
+
+	let result = Command::new(EXE)
+		.args(["-p", "./tests/testdata/operators.yall"])
+		.output()
+		.unwrap();
+
+	snapshot!("./tests/testdata/operators.yall.out", result, stdout);
 }
 
 #[test]
@@ -139,3 +165,201 @@ fn parse_whitespace() {
 
 	snapshot!("./tests/testdata/whitespace.yall.out", result, stdout);
 }
+
+#[test]
+fn parse_every_fixture() {
+	testing::before();
+
+	// Catches any fixture that was added without a test of its own
+	let files = std::fs::read_dir("./tests/testdata").unwrap();
+	for path in files.flatten().map(|file| file.path()) {
+		if path.extension() != Some("yall".as_ref()) {
+			continue;
+		}
+
+		let result = Command::new(EXE)
+			.args(["-p".as_ref(), path.as_os_str()])
+			.output()
+			.unwrap();
+
+		let out = path.with_extension("yall.out");
+		let err = path.with_extension("yall.err");
+		assert!(
+			out.exists() || err.exists(),
+			"no snapshot for {}, run the tests with SNAPSHOT=1 to create one",
+			path.display()
+		);
+		if out.exists() {
+			snapshot!(out.display().to_string(), result, stdout);
+		}
+		if err.exists() {
+			snapshot!(err.display().to_string(), result, stderr);
+		}
+	}
+}
+
+#[test]
+fn parse_nested() {
+	testing::before();
+
+	let result = Command::new(EXE)
+		.args(["-p", "./tests/testdata/nested.yall"])
+		.output()
+		.unwrap();
+
+	snapshot!("./tests/testdata/nested.yall.out", result, stdout);
+}
+
+#[test]
+fn parse_numbers() {
+	testing::before();
+
+	let result = Command::new(EXE)
+		.args(["-p", "./tests/testdata/numbers.yall"])
+		.output()
+		.unwrap();
+
+	snapshot!("./tests/testdata/numbers.yall.out", result, stdout);
+}
+
+#[test]
+fn parse_shebang() {
+	testing::before();
+
+	let result = Command::new(EXE)
+		.args(["-p", "./tests/testdata/shebang.yall"])
+		.output()
+		.unwrap();
+
+	snapshot!("./tests/testdata/shebang.yall.out", result, stdout);
+}
+
+#[test]
+fn parse_strings() {
+	testing::before();
+
+	let result = Command::new(EXE)
+		.args(["-p", "./tests/testdata/strings.yall"])
+		.output()
+		.unwrap();
+
+	snapshot!("./tests/testdata/strings.yall.out", result, stdout);
+}
+
+#[test]
+fn parse_multiple() {
+	testing::before();
+
+	let result = Command::new(EXE)
+		.args([
+			"-p",
+			"./tests/testdata/bad.yall",
+			"./tests/testdata/basic.yall",
+		])
+		.output()
+		.unwrap();
+
+	// The bad file should be reported, without stopping the good one from being parsed
+	assert!(!result.status.success());
+	assert!(String::from_utf8_lossy(&result.stderr).starts_with("./tests/testdata/bad.yall: "));
+	snapshot!("./tests/testdata/basic.yall.out", result, stdout);
+}
+
+#[test]
+fn parse_stdin() {
+	testing::before();
+
+	let mut child = Command::new(EXE)
+		.args(["-p", "-"])
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.spawn()
+		.unwrap();
+	child
+		.stdin
+		.take()
+		.unwrap()
+		.write_all(&std::fs::read("./tests/testdata/basic.yall").unwrap())
+		.unwrap();
+	let result = child.wait_with_output().unwrap();
+
+	snapshot!("./tests/testdata/basic.yall.out", result, stdout);
+}
+
+#[test]
+fn parse_empty_stdin() {
+	testing::before();
+
+	let result = Command::new(EXE)
+		.args(["-p"])
+		.stdin(Stdio::null())
+		.output()
+		.unwrap();
+
+	assert!(result.status.success());
+	snapshot!("./tests/testdata/empty.yall.out", result, stdout);
+}
+
+#[test]
+fn parse_output() {
+	testing::before();
+
+	let output = std::env::temp_dir().join("yall_parse_output.out");
+	let result = Command::new(EXE)
+		.args(["-p", "./tests/testdata/basic.yall", "-o"])
+		.arg(&output)
+		.output()
+		.unwrap();
+
+	assert!(result.status.success());
+	assert!(result.stdout.is_empty());
+	assert_eq!(
+		std::fs::read(&output).unwrap(),
+		std::fs::read("./tests/testdata/basic.yall.out").unwrap()
+	);
+	std::fs::remove_file(&output).unwrap();
+}
+
+#[test]
+fn parse_output_missing_directory() {
+	testing::before();
+
+	let result = Command::new(EXE)
+		.args([
+			"-p",
+			"./tests/testdata/basic.yall",
+			"-o",
+			"./tests/testdata/missing/basic.yall.out",
+		])
+		.output()
+		.unwrap();
+
+	assert!(!result.status.success());
+	assert!(String::from_utf8_lossy(&result.stderr)
+		.starts_with("failed to write ./tests/testdata/missing/basic.yall.out: directory ./tests/testdata/missing doesn't exist"));
+}
+
+#[test]
+fn parse_check() {
+	testing::before();
+
+	let result = Command::new(EXE)
+		.args(["--check", "./tests/testdata/basic.yall"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	assert!(result.stdout.is_empty());
+	assert!(result.stderr.is_empty());
+
+	let result = Command::new(EXE)
+		.args([
+			"--check",
+			"./tests/testdata/basic.yall",
+			"./tests/testdata/bad.yall",
+		])
+		.output()
+		.unwrap();
+	assert!(!result.status.success());
+	assert!(result.stdout.is_empty());
+	assert!(String::from_utf8_lossy(&result.stderr).starts_with("./tests/testdata/bad.yall: "));
+}
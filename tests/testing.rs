@@ -22,7 +22,7 @@ pub fn before() {
 	BUILD.call_once(|| {
 		// Build new test binary
 		Command::new("cargo")
-			.args(&["build", "--release"])
+			.args(["build", "--release"])
 			.status()
 			.expect("failed to build test binary");
 
@@ -41,12 +41,12 @@ pub fn before() {
 					.output()
 					.expect("unable to run yall");
 
-				if output.stdout.len() > 0 {
+				if !output.stdout.is_empty() {
 					fs::write(path.with_extension("yall.out"), output.stdout)
 						.expect("unable to update stdout snapshot");
 				}
 
-				if output.stderr.len() > 0 {
+				if !output.stderr.is_empty() {
 					fs::write(path.with_extension("yall.err"), output.stderr)
 						.expect("unable to update stderr snapshot");
 				}